@@ -0,0 +1,81 @@
+use rustcolony::brain::{SteeringPlan, WorldView};
+use rustcolony::config::SimulationConfig;
+use rustcolony::entity::{Entity, EntityType};
+use rustcolony::interactions::InteractionKind;
+use rustcolony::position::Vec2;
+use rustcolony::renderer::Renderer;
+use rustcolony::simulation::Simulation;
+use rustcolony::species::Species;
+use rustcolony::steering;
+
+/// Slow-drifting spores that leech energy from resources and split when full
+struct Spore;
+
+impl Species for Spore {
+    fn name(&self) -> &'static str {
+        "spore"
+    }
+
+    fn init(&self, entity: &mut Entity) {
+        entity.color = 0xB040FF;
+        entity.max_energy = 60;
+        entity.energy = 30;
+        entity.max_speed = 12.0;
+        entity.size = 2;
+    }
+
+    fn initial_population(&self) -> usize {
+        6
+    }
+
+    fn update(&self, entity: &mut Entity, _delta_time: f32) {
+        if entity.energy_consumption_timer >= 4.0 {
+            entity.energy = entity.energy.saturating_sub(1);
+            entity.energy_consumption_timer = 0.0;
+        }
+    }
+
+    fn plan(&self, entity: &Entity, world: &WorldView) -> Option<SteeringPlan> {
+        let mut plan = match entity.find_closest_entity(world.entities, EntityType::Resource, world.obstacles) {
+            Some(food) => SteeringPlan::steer(steering::seek(entity, food.position), entity.max_speed),
+            None => SteeringPlan::wander(Vec2::ZERO, entity.max_speed),
+        };
+        plan.reproduce = entity.energy == entity.max_energy;
+        Some(plan)
+    }
+
+    fn interact(&self, entity: &mut Entity, other: &mut Entity) -> Option<InteractionKind> {
+        if other.entity_type != EntityType::Resource || other.energy == 0 {
+            return None;
+        }
+        let drained = other.energy.min(5);
+        other.energy -= drained;
+        entity.energy = (entity.energy + drained).min(entity.max_energy);
+        Some(InteractionKind::Other("leech"))
+    }
+
+    fn draw(&self, entity: &Entity, renderer: &mut Renderer) -> bool {
+        let (x, y) = entity.position.to_pixel();
+        for d in -2..=2 {
+            renderer.set_pixel(x + d, y, entity.color);
+            renderer.set_pixel(x, y + d, entity.color);
+        }
+        true
+    }
+}
+
+/// Add spores to a headless colony and watch how they fare
+fn main() {
+    let mut simulation = Simulation::with_seed(800, 600, SimulationConfig::default(), 1);
+    let spore = simulation.register_species(Spore).expect("spore is not a built-in name");
+
+    for second in 0..=60 {
+        if second % 10 == 0 {
+            let spores = simulation.get_entities().iter().filter(|e| e.entity_type == spore).count();
+            println!("t={}s spores={}", second, spores);
+        }
+        for _ in 0..60 {
+            simulation.update(1.0 / 60.0);
+        }
+    }
+}
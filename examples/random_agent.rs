@@ -0,0 +1,30 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustcolony::gym::{AgentAction, ColonyEnv, EnvConfig, DISCRETE_ACTIONS};
+
+/// Run a few episodes of a gatherer taking random actions and report the returns
+fn main() {
+    let mut env = ColonyEnv::new(EnvConfig::default(), 0);
+    let mut policy = StdRng::seed_from_u64(0);
+
+    for episode in 0..3 {
+        env.reset(episode);
+        let mut total_reward = 0.0;
+        loop {
+            let action = AgentAction::Discrete(policy.gen_range(0..DISCRETE_ACTIONS));
+            let result = env.step(action).expect("actions are drawn from the valid range");
+            total_reward += result.reward;
+            if result.terminated || result.truncated {
+                let outcome = if result.terminated { "died" } else { "survived" };
+                println!(
+                    "Episode {}: {} after {} steps, return {:.2}",
+                    episode,
+                    outcome,
+                    env.steps(),
+                    total_reward
+                );
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,667 @@
+use crate::disease::HealthState;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Fits explaining less of the variance than this are flagged as not
+/// meaningful in the report
+pub const MEANINGFUL_R_SQUARED: f64 = 0.3;
+
+/// Population time series read back from a stats CSV
+#[derive(Debug, Clone, Default)]
+pub struct TimeSeries {
+    pub time: Vec<f64>,
+    /// Values per column name, one per time
+    pub columns: BTreeMap<String, Vec<f64>>,
+    /// Column names in file order
+    pub names: Vec<String>,
+}
+
+impl TimeSeries {
+    /// Parse CSV text as written by `Stats::to_csv`: a header row starting
+    /// with `time`, then one row of numbers per sample
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let header: Vec<&str> = lines.next().ok_or("The file is empty")?.split(',').map(str::trim).collect();
+        if header.first() != Some(&"time") {
+            return Err("The first column must be `time`".to_string());
+        }
+
+        let mut series = TimeSeries::default();
+        let mut values: Vec<Vec<f64>> = vec![Vec::new(); header.len() - 1];
+        for (row, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != header.len() {
+                return Err(format!("Row {} has {} fields, expected {}", row + 2, fields.len(), header.len()));
+            }
+            let number = |field: &str| {
+                field
+                    .parse::<f64>()
+                    .map_err(|_| format!("Row {} has a value that isn't a number: {}", row + 2, field))
+            };
+            series.time.push(number(fields[0])?);
+            for (column, field) in values.iter_mut().zip(&fields[1..]) {
+                column.push(number(field)?);
+            }
+        }
+
+        series.names = header[1..].iter().map(|name| name.to_string()).collect();
+        series.columns = series.names.iter().cloned().zip(values).collect();
+        Ok(series)
+    }
+
+    /// Read a stats CSV file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::from_csv(&text)?)
+    }
+
+    /// Values of a column
+    pub fn column(&self, name: &str) -> Option<&[f64]> {
+        self.columns.get(name).map(Vec::as_slice)
+    }
+
+    /// Names of the entity population columns: those before the health
+    /// state columns in a stats file, or every column in other files
+    pub fn populations(&self) -> &[String] {
+        let end = self.names.iter().position(|name| name == HealthState::NAMES[0]).unwrap_or(self.names.len());
+        &self.names[..end]
+    }
+
+    /// Average time between samples
+    fn sample_interval(&self) -> f64 {
+        match (self.time.first(), self.time.last()) {
+            (Some(first), Some(last)) if self.time.len() > 1 => (last - first) / (self.time.len() - 1) as f64,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Shape of one population's ups and downs
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleMetrics {
+    pub mean: f64,
+    /// Half the typical peak-to-trough swing
+    pub amplitude: f64,
+    /// Seconds between peaks, if the series oscillates clearly enough to tell
+    pub period: Option<f64>,
+    /// Times of the peaks of the smoothed series
+    pub peaks: Vec<f64>,
+}
+
+/// Lotka–Volterra parameters fitted to a predator–prey pair:
+/// prey' = alpha·prey − beta·prey·predators,
+/// predators' = delta·prey·predators − gamma·predators
+#[derive(Debug, Clone, PartialEq)]
+pub struct LotkaVolterraFit {
+    pub alpha: f64,
+    pub beta: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    /// Share of the variance in per-capita growth the fit explains, for prey
+    /// and predators
+    pub prey_r_squared: f64,
+    pub predator_r_squared: f64,
+}
+
+impl LotkaVolterraFit {
+    /// Whether both halves of the fit explain enough of the growth rates to
+    /// be worth reading
+    pub fn is_meaningful(&self) -> bool {
+        self.prey_r_squared >= MEANINGFUL_R_SQUARED && self.predator_r_squared >= MEANINGFUL_R_SQUARED
+    }
+
+    /// Populations the model would rest at, as (prey, predators)
+    pub fn equilibrium(&self) -> Option<(f64, f64)> {
+        let positive = [self.alpha, self.beta, self.delta, self.gamma].iter().all(|p| *p > 0.0);
+        positive.then(|| (self.gamma / self.delta, self.alpha / self.beta))
+    }
+
+    /// Period of small oscillations around the equilibrium
+    pub fn period(&self) -> Option<f64> {
+        let product = self.alpha * self.gamma;
+        (product > 0.0).then(|| std::f64::consts::TAU / product.sqrt())
+    }
+}
+
+/// Logistic growth fitted to one population: growth = r·N·(1 − N/K)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogisticFit {
+    pub growth_rate: f64,
+    /// Carrying capacity, if growth slows as the population rises
+    pub carrying_capacity: Option<f64>,
+    pub r_squared: f64,
+}
+
+/// Everything the analysis found in a run
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub prey: String,
+    pub predator: String,
+    pub duration: f64,
+    pub samples: usize,
+    pub cycles: BTreeMap<String, CycleMetrics>,
+    /// Mean delay from a prey peak to the next predator peak
+    pub phase_lag: Option<f64>,
+    pub lotka_volterra: Option<LotkaVolterraFit>,
+    /// Prey carrying capacity with predation taken out:
+    /// prey' = prey·(r − r·prey/K − beta·predators)
+    pub prey_capacity: Option<LogisticFit>,
+    pub logistic: BTreeMap<String, LogisticFit>,
+}
+
+/// Analyse the cycles of every population column in a series, and the
+/// interplay of a prey and a predator column. `smoothing` is the width in
+/// samples of the moving average taken before looking for peaks and rates.
+pub fn analyze(series: &TimeSeries, prey: &str, predator: &str, smoothing: usize) -> Result<Analysis, String> {
+    for name in [prey, predator] {
+        if series.column(name).is_none() {
+            return Err(format!("No `{}` column in the series", name));
+        }
+    }
+    if series.time.len() < 3 {
+        return Err("Too few samples to analyse".to_string());
+    }
+
+    let interval = series.sample_interval();
+    let smoothed: BTreeMap<&str, Vec<f64>> = series
+        .columns
+        .iter()
+        .map(|(name, values)| (name.as_str(), moving_average(values, smoothing)))
+        .collect();
+
+    let mut populations: Vec<&String> = series.populations().iter().collect();
+    for name in [prey, predator] {
+        if !populations.iter().any(|population| population.as_str() == name) {
+            populations.extend(series.names.iter().find(|column| column.as_str() == name));
+        }
+    }
+
+    let cycles: BTreeMap<String, CycleMetrics> = populations
+        .iter()
+        .copied()
+        .map(|name| (name.clone(), cycle_metrics(&smoothed[name.as_str()], &series.time, interval)))
+        .collect();
+    let phase_lag = mean_lag(&cycles[prey].peaks, &cycles[predator].peaks);
+
+    let prey_smooth = &smoothed[prey];
+    let predator_smooth = &smoothed[predator];
+    let prey_rates = per_capita_rates(prey_smooth, &series.time);
+    let predator_rates = per_capita_rates(predator_smooth, &series.time);
+
+    // Per-capita growth is linear in the other population under Lotka–Volterra
+    let paired = |rates: &[(usize, f64)], other: &[f64]| -> (Vec<f64>, Vec<f64>) {
+        rates.iter().filter(|(i, _)| other[*i] > 0.0).map(|&(i, rate)| (other[i], rate)).unzip()
+    };
+    let (predators_seen, prey_growth) = paired(&prey_rates, predator_smooth);
+    let (prey_seen, predator_growth) = paired(&predator_rates, prey_smooth);
+    let lotka_volterra = match (linear_fit(&predators_seen, &prey_growth), linear_fit(&prey_seen, &predator_growth)) {
+        (Some(prey_fit), Some(predator_fit)) => Some(LotkaVolterraFit {
+            alpha: prey_fit.0,
+            beta: -prey_fit.1,
+            delta: predator_fit.1,
+            gamma: -predator_fit.0,
+            prey_r_squared: prey_fit.2,
+            predator_r_squared: predator_fit.2,
+        }),
+        _ => None,
+    };
+
+    let prey_capacity = {
+        let rows: Vec<[f64; 3]> = prey_rates.iter().map(|&(i, _)| [1.0, prey_smooth[i], predator_smooth[i]]).collect();
+        let targets: Vec<f64> = prey_rates.iter().map(|&(_, rate)| rate).collect();
+        least_squares(&rows, &targets).map(|(coefficients, r_squared)| logistic(coefficients[0], coefficients[1], r_squared))
+    };
+
+    let logistic_fits = populations
+        .iter()
+        .copied()
+        .filter_map(|name| {
+            let values = &smoothed[name.as_str()];
+            let rates = per_capita_rates(values, &series.time);
+            let densities: Vec<f64> = rates.iter().map(|&(i, _)| values[i]).collect();
+            let growth: Vec<f64> = rates.iter().map(|&(_, rate)| rate).collect();
+            let (intercept, slope, r_squared) = linear_fit(&densities, &growth)?;
+            Some((name.clone(), logistic(intercept, slope, r_squared)))
+        })
+        .collect();
+
+    Ok(Analysis {
+        prey: prey.to_string(),
+        predator: predator.to_string(),
+        duration: series.time.last().unwrap_or(&0.0) - series.time.first().unwrap_or(&0.0),
+        samples: series.time.len(),
+        cycles,
+        phase_lag,
+        lotka_volterra,
+        prey_capacity,
+        logistic: logistic_fits,
+    })
+}
+
+impl Analysis {
+    /// The findings as a readable report
+    pub fn report(&self) -> String {
+        let optional = |value: Option<f64>, unit: &str| match value {
+            Some(value) => format!("{:.1}{}", value, unit),
+            None => "n/a".to_string(),
+        };
+
+        let mut report = String::new();
+        let _ = writeln!(report, "Run of {:.1}s in {} samples", self.duration, self.samples);
+        let _ = writeln!(report);
+        let _ = writeln!(report, "{:<24}{:>10}{:>11}{:>10}{:>7}{:>12}", "population", "mean", "amplitude", "period", "peaks", "capacity");
+        for (name, cycle) in &self.cycles {
+            let capacity = self.logistic.get(name).and_then(|fit| fit.carrying_capacity);
+            let _ = writeln!(
+                report,
+                "{:<24}{:>10.1}{:>11.1}{:>10}{:>7}{:>12}",
+                name,
+                cycle.mean,
+                cycle.amplitude,
+                optional(cycle.period, "s"),
+                cycle.peaks.len(),
+                optional(capacity, ""),
+            );
+        }
+
+        let _ = writeln!(report);
+        let _ = writeln!(report, "Predator–prey: {} eating {}", self.predator, self.prey);
+        let _ = write!(report, "  {} peaks lag {} peaks by {}", self.predator, self.prey, optional(self.phase_lag, "s"));
+        match (self.phase_lag, self.cycles[&self.prey].period) {
+            (Some(lag), Some(period)) => {
+                let _ = write!(report, " ({:.2} of a cycle; Lotka–Volterra predicts 0.25)", lag / period);
+            }
+            (None, _) => {
+                let _ = write!(report, " (no {} peak follows a {} peak)", self.predator, self.prey);
+            }
+            (Some(_), None) => {
+                let _ = write!(report, " ({} don't cycle clearly enough to compare)", self.prey);
+            }
+        }
+        let _ = writeln!(report);
+
+        match &self.lotka_volterra {
+            Some(fit) => {
+                let _ = writeln!(report, "  Lotka–Volterra fit:");
+                let _ = writeln!(
+                    report,
+                    "    prey growth alpha = {:.4}/s, predation beta = {:.5} (R² {:.2})",
+                    fit.alpha, fit.beta, fit.prey_r_squared
+                );
+                let _ = writeln!(
+                    report,
+                    "    predator gain delta = {:.5}, death gamma = {:.4}/s (R² {:.2})",
+                    fit.delta, fit.gamma, fit.predator_r_squared
+                );
+                match fit.equilibrium() {
+                    Some((prey, predators)) => {
+                        let _ = writeln!(report, "    equilibrium {:.1} {} and {:.1} {}", prey, self.prey, predators, self.predator);
+                    }
+                    None => {
+                        let _ = writeln!(report, "    no coexistence equilibrium: some fitted rates have the wrong sign");
+                    }
+                }
+                let _ = writeln!(report, "    predicted cycle period {}", optional(fit.period(), "s"));
+                if !fit.is_meaningful() {
+                    let _ = writeln!(
+                        report,
+                        "    fit not meaningful: R² below {} means the populations don't follow Lotka–Volterra",
+                        MEANINGFUL_R_SQUARED
+                    );
+                }
+            }
+            None => {
+                let _ = writeln!(report, "  Lotka–Volterra fit: not enough variation to fit");
+            }
+        }
+
+        if let Some(fit) = &self.prey_capacity {
+            let _ = writeln!(
+                report,
+                "  {} carrying capacity without predation {} (growth rate {:.4}/s, R² {:.2})",
+                self.prey,
+                optional(fit.carrying_capacity, ""),
+                fit.growth_rate,
+                fit.r_squared
+            );
+            if fit.r_squared < MEANINGFUL_R_SQUARED {
+                let _ = writeln!(report, "    fit not meaningful: R² below {}", MEANINGFUL_R_SQUARED);
+            }
+        }
+        report
+    }
+}
+
+/// Logistic parameters from a per-capita growth line `intercept + slope·N`
+fn logistic(intercept: f64, slope: f64, r_squared: f64) -> LogisticFit {
+    LogisticFit {
+        growth_rate: intercept,
+        carrying_capacity: (intercept > 0.0 && slope < 0.0).then(|| -intercept / slope),
+        r_squared,
+    }
+}
+
+/// Centred moving average over `window` samples, narrower at the ends
+fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            let span = &values[i.saturating_sub(half)..(i + half + 1).min(values.len())];
+            span.iter().sum::<f64>() / span.len() as f64
+        })
+        .collect()
+}
+
+/// Mean, amplitude, period and peaks of a smoothed series. The period comes
+/// from the autocorrelation's first peak after it has dipped below zero.
+fn cycle_metrics(values: &[f64], time: &[f64], interval: f64) -> CycleMetrics {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let deviations: Vec<f64> = values.iter().map(|v| v - mean).collect();
+    let variance: f64 = deviations.iter().map(|d| d * d).sum();
+
+    let mut period = None;
+    if variance > 0.0 {
+        let autocorrelation = |lag: usize| {
+            deviations.iter().zip(&deviations[lag..]).map(|(a, b)| a * b).sum::<f64>() / variance
+        };
+        let lags: Vec<f64> = (0..values.len() / 2).map(autocorrelation).collect();
+        if let Some(dip) = lags.iter().position(|c| *c < 0.0) {
+            let best = (dip..lags.len()).max_by(|a, b| lags[*a].total_cmp(&lags[*b]));
+            if let Some(best) = best.filter(|&lag| lags[lag] > 0.1 && lag + 1 < lags.len()) {
+                period = Some(best as f64 * interval);
+            }
+        }
+    }
+
+    // A peak is the highest point within a quarter period either side
+    let reach = period.map_or(2, |p| ((p / interval) / 4.0).round().max(1.0) as usize);
+    let extremes = |better: fn(f64, f64) -> bool| -> Vec<usize> {
+        (reach..values.len().saturating_sub(reach))
+            .filter(|&i| (i - reach..=i + reach).all(|j| j == i || better(values[i], values[j])))
+            .collect()
+    };
+    let peaks = extremes(|a, b| a > b);
+    let troughs = extremes(|a, b| a < b);
+
+    let average = |indices: &[usize]| indices.iter().map(|&i| values[i]).sum::<f64>() / indices.len() as f64;
+    let amplitude = if peaks.is_empty() || troughs.is_empty() {
+        let max = values.iter().copied().fold(f64::MIN, f64::max);
+        let min = values.iter().copied().fold(f64::MAX, f64::min);
+        (max - min) / 2.0
+    } else {
+        (average(&peaks) - average(&troughs)) / 2.0
+    };
+
+    CycleMetrics { mean, amplitude, period, peaks: peaks.iter().map(|&i| time[i]).collect() }
+}
+
+/// Mean delay from each leading peak to the first following peak that comes
+/// before the next leading peak
+fn mean_lag(leading: &[f64], following: &[f64]) -> Option<f64> {
+    let delays: Vec<f64> = leading
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &peak)| {
+            let next_leading = leading.get(i + 1).copied().unwrap_or(f64::INFINITY);
+            following.iter().find(|&&f| f >= peak && f < next_leading).map(|f| f - peak)
+        })
+        .collect();
+    (!delays.is_empty()).then(|| delays.iter().sum::<f64>() / delays.len() as f64)
+}
+
+/// Per-capita growth rate (dN/dt)/N at each interior sample where the
+/// population is alive, by central differences
+fn per_capita_rates(values: &[f64], time: &[f64]) -> Vec<(usize, f64)> {
+    (1..values.len().saturating_sub(1))
+        .filter(|&i| values[i] > 0.0 && time[i + 1] > time[i - 1])
+        .map(|i| (i, (values[i + 1] - values[i - 1]) / (time[i + 1] - time[i - 1]) / values[i]))
+        .collect()
+}
+
+/// Least-squares line through points, as (intercept, slope, R²)
+fn linear_fit(xs: &[f64], ys: &[f64]) -> Option<(f64, f64, f64)> {
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let spread: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if xs.len() < 3 || spread <= f64::EPSILON {
+        return None;
+    }
+    let slope = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * y).sum::<f64>() / spread;
+    let intercept = ys.iter().sum::<f64>() / n - slope * mean_x;
+    Some((intercept, slope, r_squared(ys, |i| intercept + slope * xs[i])))
+}
+
+/// Least-squares fit of `targets` to three regressors per row, by solving
+/// the normal equations, as (coefficients, R²)
+fn least_squares(rows: &[[f64; 3]], targets: &[f64]) -> Option<([f64; 3], f64)> {
+    if rows.len() < 4 {
+        return None;
+    }
+    let mut matrix = [[0.0; 4]; 3];
+    for (row, target) in rows.iter().zip(targets) {
+        for i in 0..3 {
+            for j in 0..3 {
+                matrix[i][j] += row[i] * row[j];
+            }
+            matrix[i][3] += row[i] * target;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting
+    for column in 0..3 {
+        let pivot = (column..3).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        let pivot_row = matrix[column];
+        for (index, row) in matrix.iter_mut().enumerate() {
+            if index != column {
+                let factor = row[column] / pivot_row[column];
+                for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(column) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+    let coefficients = [
+        matrix[0][3] / matrix[0][0],
+        matrix[1][3] / matrix[1][1],
+        matrix[2][3] / matrix[2][2],
+    ];
+    let predict = |i: usize| (0..3).map(|k| coefficients[k] * rows[i][k]).sum::<f64>();
+    Some((coefficients, r_squared(targets, predict)))
+}
+
+/// Share of the variance in `actual` explained by `predicted(index)`
+fn r_squared(actual: &[f64], predicted: impl Fn(usize) -> f64) -> f64 {
+    let mean = actual.iter().sum::<f64>() / actual.len() as f64;
+    let total: f64 = actual.iter().map(|y| (y - mean).powi(2)).sum();
+    let residual: f64 = actual.iter().enumerate().map(|(i, y)| (y - predicted(i)).powi(2)).sum();
+    if total > 0.0 {
+        1.0 - residual / total
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::TAU;
+
+    fn close(actual: f64, expected: f64, tolerance: f64) -> bool {
+        (actual - expected).abs() <= tolerance * expected.abs().max(1.0)
+    }
+
+    /// Stats CSV text with one row per second
+    fn csv(columns: &[(&str, &dyn Fn(f64) -> f64)], seconds: usize) -> String {
+        let mut text = String::from("time");
+        for (name, _) in columns {
+            text += &format!(",{}", name);
+        }
+        for second in 0..=seconds {
+            let t = second as f64;
+            text += &format!("\n{}", t);
+            for (_, value) in columns {
+                text += &format!(",{}", value(t));
+            }
+        }
+        text
+    }
+
+    /// Prey and predator counts once a second from Lotka–Volterra with the
+    /// given rates, integrated with fourth-order Runge–Kutta
+    fn lotka_volterra(alpha: f64, beta: f64, delta: f64, gamma: f64, seconds: usize) -> TimeSeries {
+        let derivative = |(x, y): (f64, f64)| (alpha * x - beta * x * y, delta * x * y - gamma * y);
+        let step = 0.01;
+        let mut state = (60.0, 15.0);
+        let mut series = TimeSeries {
+            names: vec!["gatherer".to_string(), "predator".to_string()],
+            ..TimeSeries::default()
+        };
+        let mut prey = Vec::new();
+        let mut predators = Vec::new();
+        for second in 0..=seconds {
+            series.time.push(second as f64);
+            prey.push(state.0);
+            predators.push(state.1);
+            for _ in 0..100 {
+                let shifted = |k: (f64, f64), scale: f64| (state.0 + k.0 * scale, state.1 + k.1 * scale);
+                let k1 = derivative(state);
+                let k2 = derivative(shifted(k1, step / 2.0));
+                let k3 = derivative(shifted(k2, step / 2.0));
+                let k4 = derivative(shifted(k3, step));
+                state.0 += step / 6.0 * (k1.0 + 2.0 * k2.0 + 2.0 * k3.0 + k4.0);
+                state.1 += step / 6.0 * (k1.1 + 2.0 * k2.1 + 2.0 * k3.1 + k4.1);
+            }
+        }
+        series.columns = [("gatherer".to_string(), prey), ("predator".to_string(), predators)].into();
+        series
+    }
+
+    #[test]
+    fn linear_fit_recovers_an_exact_line() {
+        let xs: Vec<f64> = (0..20).map(f64::from).collect();
+        let ys: Vec<f64> = xs.iter().map(|x| 3.5 - 0.25 * x).collect();
+        let (intercept, slope, r_squared) = linear_fit(&xs, &ys).unwrap();
+        assert!(close(intercept, 3.5, 1e-12));
+        assert!(close(slope, -0.25, 1e-12));
+        assert!(close(r_squared, 1.0, 1e-12));
+    }
+
+    #[test]
+    fn least_squares_recovers_an_exact_plane() {
+        let rows: Vec<[f64; 3]> = (0..30).map(|i| [1.0, f64::from(i), f64::from(i * i % 7)]).collect();
+        let targets: Vec<f64> = rows.iter().map(|row| 2.0 + 0.5 * row[1] - 1.5 * row[2]).collect();
+        let (coefficients, r_squared) = least_squares(&rows, &targets).unwrap();
+        for (coefficient, expected) in coefficients.iter().zip([2.0, 0.5, -1.5]) {
+            assert!(close(*coefficient, expected, 1e-9));
+        }
+        assert!(close(r_squared, 1.0, 1e-9));
+    }
+
+    #[test]
+    fn r_squared_is_zero_for_a_mean_and_for_a_constant_series() {
+        let ys = [1.0, 3.0, 2.0, 6.0];
+        assert!(close(r_squared(&ys, |_| 3.0), 0.0, 1e-12));
+        assert_eq!(r_squared(&[4.0; 5], |_| 4.0), 0.0);
+    }
+
+    #[test]
+    fn sinusoids_give_their_period_and_phase_lag() {
+        let prey = |t: f64| 100.0 + 20.0 * (TAU * t / 48.0).sin();
+        let predator = |t: f64| 30.0 + 5.0 * (TAU * (t - 12.0) / 48.0).sin();
+        let series = TimeSeries::from_csv(&csv(&[("gatherer", &prey), ("predator", &predator)], 480)).unwrap();
+
+        let prey_cycle = cycle_metrics(series.column("gatherer").unwrap(), &series.time, 1.0);
+        let predator_cycle = cycle_metrics(series.column("predator").unwrap(), &series.time, 1.0);
+        assert_eq!(prey_cycle.period, Some(48.0));
+        assert_eq!(predator_cycle.period, Some(48.0));
+        assert!(close(prey_cycle.mean, 100.0, 0.01));
+        assert!(close(prey_cycle.amplitude, 20.0, 0.01));
+        assert_eq!(prey_cycle.peaks.first(), Some(&12.0));
+        assert_eq!(mean_lag(&prey_cycle.peaks, &predator_cycle.peaks), Some(12.0));
+
+        let analysis = analyze(&series, "gatherer", "predator", 1).unwrap();
+        assert_eq!(analysis.phase_lag, Some(12.0));
+        assert!(analysis.report().contains("0.25 of a cycle"));
+    }
+
+    #[test]
+    fn lotka_volterra_rates_are_recovered_from_an_integrated_series() {
+        let series = lotka_volterra(0.1, 0.005, 0.002, 0.08, 600);
+        let fit = analyze(&series, "gatherer", "predator", 1).unwrap().lotka_volterra.unwrap();
+        assert!(close(fit.alpha, 0.1, 0.05), "alpha {}", fit.alpha);
+        assert!(close(fit.beta, 0.005, 0.05), "beta {}", fit.beta);
+        assert!(close(fit.delta, 0.002, 0.05), "delta {}", fit.delta);
+        assert!(close(fit.gamma, 0.08, 0.05), "gamma {}", fit.gamma);
+        assert!(fit.is_meaningful());
+        let (prey, predators) = fit.equilibrium().unwrap();
+        assert!(close(prey, 40.0, 0.05) && close(predators, 20.0, 0.05));
+    }
+
+    #[test]
+    fn per_capita_rates_of_exponential_growth_are_constant() {
+        let time: Vec<f64> = (0..50).map(|i| f64::from(i) * 0.1).collect();
+        let values: Vec<f64> = time.iter().map(|t| 10.0 * (0.3 * t).exp()).collect();
+        let rates = per_capita_rates(&values, &time);
+        assert_eq!(rates.len(), values.len() - 2);
+        assert!(rates.iter().all(|&(_, rate)| close(rate, 0.3, 0.01)));
+    }
+
+    #[test]
+    fn constant_series_has_no_cycle_or_fit() {
+        let series = TimeSeries::from_csv(&csv(&[("gatherer", &|_| 25.0), ("predator", &|_| 5.0)], 100)).unwrap();
+        let cycle = cycle_metrics(series.column("gatherer").unwrap(), &series.time, 1.0);
+        assert_eq!(cycle, CycleMetrics { mean: 25.0, amplitude: 0.0, period: None, peaks: Vec::new() });
+
+        let analysis = analyze(&series, "gatherer", "predator", 1).unwrap();
+        assert_eq!(analysis.phase_lag, None);
+        assert_eq!(analysis.lotka_volterra, None);
+        let report = analysis.report();
+        assert!(report.contains("by n/a (no predator peak follows a gatherer peak)"));
+        assert!(report.contains("not enough variation to fit"));
+    }
+
+    #[test]
+    fn noise_is_flagged_as_not_meaningful() {
+        let jitter = |t: f64| ((t * 12.9898).sin() * 43758.5453).fract().abs();
+        let prey = |t: f64| 50.0 + 10.0 * jitter(t);
+        let predator = |t: f64| 10.0 + 3.0 * jitter(t + 0.5);
+        let series = TimeSeries::from_csv(&csv(&[("gatherer", &prey), ("predator", &predator)], 300)).unwrap();
+        let analysis = analyze(&series, "gatherer", "predator", 1).unwrap();
+        assert!(!analysis.lotka_volterra.as_ref().unwrap().is_meaningful());
+        assert!(analysis.report().contains("fit not meaningful"));
+    }
+
+    #[test]
+    fn too_few_samples_are_rejected() {
+        assert_eq!(linear_fit(&[1.0], &[2.0]), None);
+        assert_eq!(linear_fit(&[], &[]), None);
+        assert_eq!(least_squares(&[[1.0, 2.0, 3.0]], &[1.0]), None);
+        assert!(per_capita_rates(&[5.0], &[0.0]).is_empty());
+        assert_eq!(mean_lag(&[], &[3.0]), None);
+
+        let series = TimeSeries::from_csv("time,gatherer,predator\n0,10,2\n1,11,2\n").unwrap();
+        assert_eq!(series.sample_interval(), 1.0);
+        assert!(analyze(&series, "gatherer", "predator", 1).is_err());
+        assert!(analyze(&series, "gatherer", "wolf", 1).is_err());
+    }
+
+    #[test]
+    fn csv_is_parsed_and_malformed_rows_are_reported() {
+        let series = TimeSeries::from_csv("time,gatherer,predator,susceptible\n0,10,2,8\n\n0.5,12,3,9\n").unwrap();
+        assert_eq!(series.time, [0.0, 0.5]);
+        assert_eq!(series.column("predator"), Some(&[2.0, 3.0][..]));
+        assert_eq!(series.populations(), ["gatherer", "predator"]);
+
+        let short = TimeSeries::from_csv("time,gatherer\n0,10\n1\n").unwrap_err();
+        assert_eq!(short, "Row 3 has 1 fields, expected 2");
+        let garbled = TimeSeries::from_csv("time,gatherer\n0,ten\n").unwrap_err();
+        assert_eq!(garbled, "Row 2 has a value that isn't a number: ten");
+        assert!(TimeSeries::from_csv("gatherer,time\n10,0\n").is_err());
+        assert!(TimeSeries::from_csv("").is_err());
+    }
+}
@@ -0,0 +1,383 @@
+use crate::brain::{Blackboard, SteeringPlan, WorldView};
+use crate::entity::{Entity, EntityType, FLEE_SPEED_MULTIPLIER};
+use crate::position::Vec2;
+use crate::steering;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Distance at which arrive actions start braking
+const ARRIVE_SLOWING_RADIUS: f32 = 25.0;
+
+/// Result of ticking a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+/// A behaviour tree definition, shared by every entity it is assigned to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorTree {
+    pub root: Node,
+}
+
+/// A node of a behaviour tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Node {
+    /// Tick children in order until one does not fail
+    Selector { children: Vec<Node> },
+    /// Tick children in order until one does not succeed
+    Sequence { children: Vec<Node> },
+    /// Swap success and failure of the child
+    Inverter { child: Box<Node> },
+    /// Report success whatever the child returns
+    Succeeder { child: Box<Node> },
+    /// Fail without ticking the child until `seconds` have passed since it last succeeded
+    Cooldown { seconds: f32, child: Box<Node> },
+    /// Succeed if the check holds
+    Condition { check: Condition },
+    /// Do something, usually contribute steering
+    Action { action: Action },
+}
+
+/// Checks available to condition nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// Energy ratio is below the value
+    EnergyBelow(f32),
+    /// Energy ratio is above the value
+    EnergyAbove(f32),
+    /// A predator is within sensing range and line of sight
+    ThreatSensed,
+    /// An entity of the type is within sensing range and line of sight
+    Senses(EntityType),
+    /// Seconds since the last successful hunt exceed the value
+    HungerAbove(f32),
+    /// The entity belongs to a predator pack
+    InPack,
+    /// A blackboard entry exists and exceeds the value
+    BlackboardAbove { key: String, value: f32 },
+}
+
+/// Actions available to action nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Steer straight at the nearest sensed entity of the type
+    Seek(EntityType),
+    /// Steer at the nearest sensed entity of the type, braking on approach
+    Arrive(EntityType),
+    /// Steer at where the nearest sensed entity of the type is heading
+    Pursue(EntityType),
+    /// Evade every sensed predator at flee speed
+    Flee,
+    /// Meander randomly
+    Wander,
+    /// Brake to a standstill
+    Rest,
+    /// Keep distance from nearby entities of the same type
+    Separate(f32),
+    /// Limit speed to a fraction of the maximum
+    SetSpeed(f32),
+    /// Write a value to the blackboard
+    Set { key: String, value: f32 },
+    /// Remove a value from the blackboard
+    Clear(String),
+}
+
+/// Mutable state threaded through one tick of a tree
+struct Tick<'a, 'w> {
+    entity: &'a Entity,
+    world: &'a WorldView<'w>,
+    blackboard: &'a mut Blackboard,
+    plan: SteeringPlan,
+}
+
+impl BehaviorTree {
+    /// Load a tree from a TOML data file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read behaviour tree {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("Invalid behaviour tree {}: {}", path.display(), e).into())
+    }
+
+    /// Run the tree once for an entity and return the steering it asked for
+    pub fn tick(&self, entity: &Entity, world: &WorldView, blackboard: &mut Blackboard) -> SteeringPlan {
+        let mut tick = Tick {
+            entity,
+            world,
+            blackboard,
+            plan: SteeringPlan::steer(Vec2::ZERO, entity.max_speed),
+        };
+        self.root.tick(0, &mut tick);
+        tick.plan
+    }
+}
+
+impl Node {
+    /// Number of nodes in this subtree, used to give every node a stable index
+    fn size(&self) -> usize {
+        match self {
+            Node::Selector { children } | Node::Sequence { children } => {
+                1 + children.iter().map(Node::size).sum::<usize>()
+            }
+            Node::Inverter { child } | Node::Succeeder { child } | Node::Cooldown { child, .. } => 1 + child.size(),
+            Node::Condition { .. } | Node::Action { .. } => 1,
+        }
+    }
+
+    /// Tick the node; `index` is its position in a pre-order walk of the tree
+    fn tick(&self, index: usize, tick: &mut Tick) -> Status {
+        match self {
+            Node::Selector { children } => {
+                let mut child_index = index + 1;
+                for child in children {
+                    let status = child.tick(child_index, tick);
+                    if status != Status::Failure {
+                        return status;
+                    }
+                    child_index += child.size();
+                }
+                Status::Failure
+            }
+            Node::Sequence { children } => {
+                let mut child_index = index + 1;
+                for child in children {
+                    let status = child.tick(child_index, tick);
+                    if status != Status::Success {
+                        return status;
+                    }
+                    child_index += child.size();
+                }
+                Status::Success
+            }
+            Node::Inverter { child } => match child.tick(index + 1, tick) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Node::Succeeder { child } => {
+                child.tick(index + 1, tick);
+                Status::Success
+            }
+            Node::Cooldown { seconds, child } => {
+                let key = format!("cooldown.{}", index);
+                if let Some(last) = tick.blackboard.get(&key) {
+                    if tick.world.time - last < *seconds {
+                        return Status::Failure;
+                    }
+                }
+                let status = child.tick(index + 1, tick);
+                if status == Status::Success {
+                    tick.blackboard.insert(key, tick.world.time);
+                }
+                status
+            }
+            Node::Condition { check } => {
+                if check.holds(tick) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
+            Node::Action { action } => action.run(tick),
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, tick: &Tick) -> bool {
+        let entity = tick.entity;
+        let energy_ratio = entity.energy as f32 / entity.max_energy as f32;
+
+        match self {
+            Condition::EnergyBelow(ratio) => energy_ratio < *ratio,
+            Condition::EnergyAbove(ratio) => energy_ratio > *ratio,
+            Condition::ThreatSensed => !entity.sensed_predators(tick.world.entities, tick.world.obstacles).is_empty(),
+            Condition::Senses(kind) => entity
+                .find_closest_entity(tick.world.entities, *kind, tick.world.obstacles)
+                .is_some(),
+            Condition::HungerAbove(seconds) => entity.time_since_last_hunt > *seconds,
+            Condition::InPack => entity.pack_id.is_some(),
+            Condition::BlackboardAbove { key, value } => tick.blackboard.get(key).is_some_and(|v| v > value),
+        }
+    }
+}
+
+impl Action {
+    fn run(&self, tick: &mut Tick) -> Status {
+        let entity = tick.entity;
+        let world = tick.world;
+
+        match self {
+            Action::Seek(kind) | Action::Arrive(kind) | Action::Pursue(kind) => {
+                let Some(target) = entity.find_closest_entity(world.entities, *kind, world.obstacles) else {
+                    return Status::Failure;
+                };
+                tick.plan.force += match self {
+                    Action::Seek(_) => steering::seek(entity, target.position),
+                    Action::Arrive(_) => steering::arrive(entity, target.position, ARRIVE_SLOWING_RADIUS),
+                    _ => steering::pursue(entity, target),
+                };
+                if entity.can_interact_with(target) {
+                    Status::Success
+                } else {
+                    Status::Running
+                }
+            }
+            Action::Flee => {
+                let threats = entity.sensed_predators(world.entities, world.obstacles);
+                if threats.is_empty() {
+                    return Status::Failure;
+                }
+                for predator in threats {
+                    tick.plan.force += steering::evade(entity, predator);
+                }
+                tick.plan.fleeing = true;
+                tick.plan.speed_limit = entity.max_speed * FLEE_SPEED_MULTIPLIER;
+                Status::Running
+            }
+            Action::Wander => {
+                tick.plan.wander = true;
+                Status::Success
+            }
+            Action::Rest => {
+                tick.plan.force += entity.velocity * -1.0;
+                tick.plan.speed_limit = 0.0;
+                Status::Success
+            }
+            Action::Separate(radius) => {
+                let same_kind = world.entities.iter().filter(|e| e.entity_type == entity.entity_type);
+                tick.plan.force += steering::separation(entity, same_kind, *radius);
+                Status::Success
+            }
+            Action::SetSpeed(fraction) => {
+                tick.plan.speed_limit = entity.max_speed * fraction.clamp(0.0, FLEE_SPEED_MULTIPLIER);
+                Status::Success
+            }
+            Action::Set { key, value } => {
+                tick.blackboard.insert(key.clone(), *value);
+                Status::Success
+            }
+            Action::Clear(key) => {
+                tick.blackboard.remove(key);
+                Status::Success
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    fn tree(text: &str) -> BehaviorTree {
+        toml::from_str(text).unwrap()
+    }
+
+    fn run(tree: &BehaviorTree, entities: &[Entity], time: f32, blackboard: &mut Blackboard) -> SteeringPlan {
+        let world = WorldView { entities, obstacles: &[], time };
+        tree.tick(&entities[0], &world, blackboard)
+    }
+
+    fn gatherer(energy: u32) -> Entity {
+        Entity { energy, ..Entity::new_gatherer(1, Position::new(100.0, 100.0)) }
+    }
+
+    #[test]
+    fn selectors_stop_at_the_first_child_that_does_not_fail() {
+        let tree = tree(
+            r#"
+            [root]
+            type = "selector"
+            children = [
+                { type = "condition", check = { energy_below = 0.5 } },
+                { type = "action", action = { set = { key = "second", value = 1.0 } } },
+                { type = "action", action = { set = { key = "third", value = 1.0 } } },
+            ]
+            "#,
+        );
+        let mut blackboard = Blackboard::new();
+        run(&tree, &[gatherer(50)], 0.0, &mut blackboard);
+        assert!(blackboard.is_empty());
+
+        run(&tree, &[gatherer(150)], 0.0, &mut blackboard);
+        assert_eq!(blackboard.keys().collect::<Vec<_>>(), ["second"]);
+    }
+
+    #[test]
+    fn sequences_stop_at_the_first_child_that_does_not_succeed() {
+        let tree = tree(
+            r#"
+            [root]
+            type = "sequence"
+            children = [
+                { type = "action", action = { set = { key = "first", value = 1.0 } } },
+                { type = "inverter", child = { type = "condition", check = "in_pack" } },
+                { type = "condition", check = { energy_above = 0.5 } },
+                { type = "action", action = { set = { key = "last", value = 1.0 } } },
+            ]
+            "#,
+        );
+        let mut blackboard = Blackboard::new();
+        run(&tree, &[gatherer(50)], 0.0, &mut blackboard);
+        assert_eq!(blackboard.keys().collect::<Vec<_>>(), ["first"]);
+
+        run(&tree, &[gatherer(150)], 0.0, &mut blackboard);
+        assert!(blackboard.contains_key("last"));
+    }
+
+    #[test]
+    fn cooldowns_fail_until_their_time_has_passed() {
+        let tree = tree(
+            r#"
+            [root]
+            type = "selector"
+            children = [
+                { type = "cooldown", seconds = 5.0, child = { type = "action", action = "wander" } },
+                { type = "action", action = "rest" },
+            ]
+            "#,
+        );
+        let mut blackboard = Blackboard::new();
+        let entities = [gatherer(100)];
+        assert!(run(&tree, &entities, 0.0, &mut blackboard).wander);
+        assert!(!run(&tree, &entities, 4.0, &mut blackboard).wander);
+        assert!(run(&tree, &entities, 5.0, &mut blackboard).wander);
+    }
+
+    #[test]
+    fn bundled_trees_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("behaviors");
+        for name in ["gatherer.toml", "predator.toml"] {
+            BehaviorTree::load(dir.join(name)).unwrap();
+        }
+        assert!(BehaviorTree::load(dir.join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn bundled_gatherer_tree_flees_rests_and_forages() {
+        let tree = BehaviorTree::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("behaviors/gatherer.toml")).unwrap();
+        let resource = Entity::new_resource(2, Position::new(160.0, 100.0));
+        let predator = Entity::new_predator(3, Position::new(60.0, 100.0));
+        let mut blackboard = Blackboard::new();
+
+        let plan = run(&tree, &[gatherer(100), resource.clone(), predator], 0.0, &mut blackboard);
+        assert!(plan.fleeing);
+        assert!(plan.force.x > 0.0);
+
+        let plan = run(&tree, &[gatherer(195), resource.clone()], 0.0, &mut blackboard);
+        assert_eq!(plan.speed_limit, 0.0);
+
+        let hungry = gatherer(40);
+        let plan = run(&tree, &[hungry.clone(), resource], 0.0, &mut blackboard);
+        assert!(!plan.fleeing);
+        assert!(plan.force.x > 0.0);
+        assert_eq!(plan.speed_limit, hungry.max_speed * 0.9);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::behavior_tree::BehaviorTree;
+use crate::entity::{Entity, EntityType};
+use crate::neural::HallOfFame;
+use crate::obstacle::Obstacle;
+use crate::position::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Per-entity memory that brains can read and write between ticks
+pub type Blackboard = BTreeMap<String, f32>;
+
+/// Length of the vector returned by `sense`
+pub const SENSOR_COUNT: usize = 9;
+
+/// Read-only view of the world handed to brains when they decide
+pub struct WorldView<'a> {
+    pub entities: &'a [Entity],
+    pub obstacles: &'a [Obstacle],
+    pub time: f32,
+}
+
+/// Movement intent computed from a read-only view of the world
+#[derive(Debug, Clone, Copy)]
+pub struct SteeringPlan {
+    pub force: Vec2,
+    pub wander: bool,
+    pub speed_limit: f32,
+    pub fleeing: bool,
+    /// Whether the entity wants to split off a child this tick
+    pub reproduce: bool,
+}
+
+impl SteeringPlan {
+    /// Steer with a force at up to the given speed
+    pub fn steer(force: Vec2, speed_limit: f32) -> Self {
+        SteeringPlan { force, wander: false, speed_limit, fleeing: false, reproduce: false }
+    }
+
+    /// Wander on top of a force at up to the given speed
+    pub fn wander(force: Vec2, speed_limit: f32) -> Self {
+        SteeringPlan { force, wander: true, speed_limit, fleeing: false, reproduce: false }
+    }
+}
+
+/// Which decision system drives an entity type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrainKind {
+    /// The built-in hand-written behaviours
+    #[default]
+    Default,
+    /// A behaviour tree loaded from a data file
+    BehaviorTree,
+    /// Utility scoring of candidate actions, tuned in the `[utility]` config section
+    Utility,
+    /// Evolving neural networks, optionally seeded from saved champions in `file`
+    Neural,
+    /// A function in the `[scripting]` script, `behave_<type>` unless `function` is set
+    Script,
+}
+
+/// Brain assignment for one entity type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrainSpec {
+    pub kind: BrainKind,
+    /// Data file the brain is loaded from, relative to the config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Behaviour tree, filled in from `file` when the config is loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behavior_tree: Option<BehaviorTree>,
+    /// Saved networks to seed new entities from, filled in from `file`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_brains: Option<HallOfFame>,
+    /// Script function that steers entities with `script` brains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+impl BrainSpec {
+    /// Load any data file the brain refers to, resolving it against `base_dir`
+    pub fn resolve(&mut self, base_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(file) = &self.file {
+            let path = base_dir.join(file);
+            if self.kind == BrainKind::BehaviorTree && self.behavior_tree.is_none() {
+                self.behavior_tree = Some(BehaviorTree::load(&path)?);
+            }
+            if self.kind == BrainKind::Neural && self.seed_brains.is_none() {
+                self.seed_brains = Some(HallOfFame::load(&path)?);
+            }
+        }
+
+        if self.kind == BrainKind::BehaviorTree && self.behavior_tree.is_none() {
+            return Err("behavior_tree brains need a `file` with the tree definition".into());
+        }
+        Ok(())
+    }
+}
+
+/// Build the sensor vector for an entity: direction and proximity of the
+/// nearest food (prey for predators, carcasses for scavengers) and threat, own energy and velocity
+pub fn sense(entity: &Entity, world: &WorldView) -> [f32; SENSOR_COUNT] {
+    let target_type = match entity.entity_type {
+        EntityType::Predator => EntityType::Gatherer,
+        EntityType::Scavenger => EntityType::Carcass,
+        _ => EntityType::Resource,
+    };
+    let target = entity.find_closest_entity(world.entities, target_type, world.obstacles);
+    let threat = entity
+        .sensed_predators(world.entities, world.obstacles)
+        .into_iter()
+        .min_by(|a, b| {
+            entity
+                .position
+                .distance_squared_to(&a.position)
+                .total_cmp(&entity.position.distance_squared_to(&b.position))
+        });
+
+    let [target_x, target_y, target_proximity] = direction_and_proximity(entity, target);
+    let [threat_x, threat_y, threat_proximity] = direction_and_proximity(entity, threat);
+    let speed_scale = entity.max_speed.max(1.0);
+
+    [
+        target_x,
+        target_y,
+        target_proximity,
+        threat_x,
+        threat_y,
+        threat_proximity,
+        entity.energy as f32 / entity.max_energy as f32,
+        entity.velocity.x / speed_scale,
+        entity.velocity.y / speed_scale,
+    ]
+}
+
+/// Unit direction to another entity and how close it is (1 touching, 0 out of range)
+fn direction_and_proximity(entity: &Entity, other: Option<&Entity>) -> [f32; 3] {
+    match other {
+        Some(other) => {
+            let offset = entity.position.vector_to(&other.position);
+            let direction = offset.normalized();
+            let proximity = 1.0 - (offset.length() / entity.sensing_radius.max(1.0)).min(1.0);
+            [direction.x, direction.y, proximity]
+        }
+        None => [0.0, 0.0, 0.0],
+    }
+}
@@ -0,0 +1,157 @@
+use rustcolony::config::Preset;
+use std::path::PathBuf;
+
+/// Command line options for the simulation
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    pub config_path: Option<PathBuf>,
+    pub save_brains_path: Option<PathBuf>,
+    /// Overrides the preset in the config file
+    pub preset: Option<Preset>,
+    pub stats_path: Option<PathBuf>,
+    /// Print the energy ledger on exit
+    pub energy_report: bool,
+    /// Record the run and its interventions here on exit
+    pub record_path: Option<PathBuf>,
+    /// Play back a recorded run instead of starting a new one
+    pub replay_path: Option<PathBuf>,
+    /// Seconds into the replay to start from
+    pub seek: Option<f32>,
+    /// Analyse a recorded run instead of running the simulation
+    pub analyze: Option<AnalyzeOptions>,
+}
+
+/// Options of the `analyze` subcommand
+#[derive(Debug)]
+pub struct AnalyzeOptions {
+    pub stats_path: PathBuf,
+    pub prey: String,
+    pub predator: String,
+    /// Width in samples of the moving average applied before analysis
+    pub smoothing: usize,
+}
+
+impl CliOptions {
+    /// Parse options from the process arguments
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parse options from an argument list (without the program name)
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = CliOptions::default();
+        let mut args = args.into_iter().peekable();
+        if args.peek().is_some_and(|arg| arg == "analyze") {
+            args.next();
+            options.analyze = Some(Self::parse_analyze(args)?);
+            return Ok(options);
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" | "-c" => {
+                    let path = args.next().ok_or("--config requires a file path")?;
+                    options.config_path = Some(PathBuf::from(path));
+                }
+                "--save-brains" => {
+                    let path = args.next().ok_or("--save-brains requires a file path")?;
+                    options.save_brains_path = Some(PathBuf::from(path));
+                }
+                "--stats" => {
+                    let path = args.next().ok_or("--stats requires a file path")?;
+                    options.stats_path = Some(PathBuf::from(path));
+                }
+                "--energy-report" => options.energy_report = true,
+                "--record" => {
+                    let path = args.next().ok_or("--record requires a file path")?;
+                    options.record_path = Some(PathBuf::from(path));
+                }
+                "--replay" => {
+                    let path = args.next().ok_or("--replay requires a file path")?;
+                    options.replay_path = Some(PathBuf::from(path));
+                }
+                "--seek" => {
+                    let seconds = args.next().ok_or("--seek requires a number of seconds")?;
+                    let seconds = seconds
+                        .parse()
+                        .map_err(|_| format!("--seek expects a number of seconds, got {}", seconds))?;
+                    options.seek = Some(seconds);
+                }
+                "--preset" => {
+                    let name = args.next().ok_or("--preset requires a name")?;
+                    let preset = Preset::from_name(&name).ok_or_else(|| {
+                        let names: Vec<&str> = Preset::ALL.iter().map(|p| p.name()).collect();
+                        format!("Unknown preset: {} (expected one of {})", name, names.join(", "))
+                    })?;
+                    options.preset = Some(preset);
+                }
+                "--help" | "-h" => return Err(Self::usage()),
+                other => return Err(format!("Unknown argument: {}\n\n{}", other, Self::usage())),
+            }
+        }
+
+        if options.replay_path.is_some() {
+            if options.config_path.is_some() || options.preset.is_some() || options.record_path.is_some() {
+                return Err("--replay uses the recorded settings and can't be combined with --config, --preset or --record".to_string());
+            }
+        } else if options.seek.is_some() {
+            return Err("--seek only applies with --replay".to_string());
+        }
+
+        Ok(options)
+    }
+
+    /// Parse the arguments following `analyze`
+    fn parse_analyze(mut args: impl Iterator<Item = String>) -> Result<AnalyzeOptions, String> {
+        let mut stats_path = None;
+        let mut prey = "gatherer".to_string();
+        let mut predator = "predator".to_string();
+        let mut smoothing = 5;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--prey" => prey = args.next().ok_or("--prey requires a column name")?,
+                "--predator" => predator = args.next().ok_or("--predator requires a column name")?,
+                "--smooth" => {
+                    let samples = args.next().ok_or("--smooth requires a number of samples")?;
+                    smoothing = samples
+                        .parse()
+                        .map_err(|_| format!("--smooth expects a whole number of samples, got {}", samples))?;
+                }
+                "--help" | "-h" => return Err(Self::usage()),
+                other if other.starts_with('-') => {
+                    return Err(format!("Unknown argument: {}\n\n{}", other, Self::usage()))
+                }
+                path => stats_path = Some(PathBuf::from(path)),
+            }
+        }
+
+        let stats_path = stats_path.ok_or_else(|| format!("analyze requires a stats CSV file\n\n{}", Self::usage()))?;
+        Ok(AnalyzeOptions { stats_path, prey, predator, smoothing })
+    }
+
+    /// Usage text shown for --help and argument errors
+    pub fn usage() -> String {
+        [
+            "Usage: rustcolony [OPTIONS]",
+            "       rustcolony analyze <FILE> [--prey NAME] [--predator NAME] [--smooth N]",
+            "",
+            "Options:",
+            "  -c, --config <FILE>       Load simulation settings from a TOML file",
+            "      --preset <NAME>       Species set to start with: classic or food_web",
+            "      --save-brains <FILE>  Save the best neural brains here on exit or when B is pressed",
+            "      --stats <FILE>        Save population and epidemic time series here as CSV on exit",
+            "      --energy-report       Print where the world's energy came from and went on exit",
+            "      --record <FILE>       Save a replay of the run and every key-press intervention here on exit",
+            "      --replay <FILE>       Play back a recorded run instead of starting a new one",
+            "      --seek <SECONDS>      Start the replay this far in",
+            "  -h, --help                Show this help",
+            "",
+            "analyze reports population cycles, predator-prey phase lag, a Lotka-Volterra",
+            "fit and carrying capacities for a run saved with --stats. --prey and",
+            "--predator name the columns to pair (default gatherer and predator) and",
+            "--smooth the moving-average width in samples (default 5).",
+        ]
+        .join("\n")
+    }
+}
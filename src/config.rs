@@ -0,0 +1,699 @@
+use crate::brain::{BrainKind, BrainSpec};
+use crate::entity::{Entity, EntityType};
+use crate::food_web;
+use crate::interactions::{InteractionRule, InteractionTable};
+use crate::utility::UtilityProfile;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Most obstacles a config may ask for, which at their average size cover
+/// about half of the 800×600 window
+pub const MAX_OBSTACLES: usize = 100;
+
+/// Tunable simulation parameters, loadable from a TOML file.
+///
+/// Every section and field is optional in the file; anything left out keeps
+/// its default value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationConfig {
+    /// Seed for the random number generator; a fresh one is drawn when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Set of species the world starts with
+    pub preset: Preset,
+    /// Rocks scattered at the start that block movement and line of sight
+    pub obstacles: usize,
+    /// Keep the console free of the running account of hunts, births and deaths
+    pub quiet: bool,
+    pub flocking: FlockingConfig,
+    pub packs: PackConfig,
+    pub nests: NestConfig,
+    /// Competing gatherer colonies; without any, all gatherers form one colony
+    pub factions: Vec<FactionConfig>,
+    pub pheromones: PheromoneConfig,
+    pub territory: TerritoryConfig,
+    /// Decision system per entity type, keyed by type name (e.g. `gatherer`)
+    pub brains: BTreeMap<String, BrainSpec>,
+    /// Overrides of the built-in utility scoring, keyed by type name
+    pub utility: BTreeMap<String, UtilityProfile>,
+    pub neural: NeuralConfig,
+    pub scripting: ScriptConfig,
+    pub carcasses: CarcassConfig,
+    pub disease: DiseaseConfig,
+    pub metabolism: MetabolismConfig,
+    pub life: LifeConfig,
+    pub stats: StatsConfig,
+    pub energy: EnergyConfig,
+    pub timeline: TimelineConfig,
+    /// Interaction rules laid over the built-in food web, matched by actor and target type
+    pub interactions: Vec<InteractionRule>,
+}
+
+impl SimulationConfig {
+    /// Load a configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let mut config: SimulationConfig = toml::from_str(&text)?;
+
+        // Data files referenced by the config are relative to it
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        for (entity_type, brain) in &mut config.brains {
+            brain
+                .resolve(base_dir)
+                .map_err(|e| format!("Brain for {}: {}", entity_type, e))?;
+        }
+        for faction in &mut config.factions {
+            if let Some(brain) = &mut faction.brain {
+                if brain.kind == BrainKind::Script {
+                    return Err(format!("Faction {}: script brains can only be set per entity type", faction.name).into());
+                }
+                brain
+                    .resolve(base_dir)
+                    .map_err(|e| format!("Brain for faction {}: {}", faction.name, e))?;
+            }
+        }
+        if let Some(file) = &config.scripting.file {
+            config.scripting.file = Some(base_dir.join(file));
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check for settings the simulation can't run with
+    pub fn validate(&self) -> Result<(), String> {
+        if self.obstacles > MAX_OBSTACLES {
+            return Err(format!("obstacles = {} would leave little room to move; at most {} are allowed", self.obstacles, MAX_OBSTACLES));
+        }
+        if !(0.0..=1.0).contains(&self.neural.mutation_rate) {
+            return Err(format!("[neural] mutation_rate must be between 0 and 1, not {}", self.neural.mutation_rate));
+        }
+        if !(self.neural.mutation_strength.is_finite() && self.neural.mutation_strength >= 0.0) {
+            return Err(format!("[neural] mutation_strength must be 0 or more, not {}", self.neural.mutation_strength));
+        }
+        Ok(())
+    }
+
+    /// The built-in interaction rules and the preset's with the configured
+    /// ones laid over them
+    pub fn interaction_table(&self) -> InteractionTable {
+        InteractionTable::new(
+            InteractionRule::defaults()
+                .into_iter()
+                .chain(self.preset.interactions())
+                .chain(self.interactions.iter().cloned()),
+        )
+    }
+
+    /// Brain driving an entity: its faction's, if set, otherwise its type's
+    pub fn brain_for(&self, entity: &Entity) -> Option<&BrainSpec> {
+        entity
+            .faction
+            .and_then(|id| self.factions.get(id))
+            .and_then(|faction| faction.brain.as_ref())
+            .or_else(|| self.brains.get(entity.entity_type.name()))
+    }
+
+    /// Herding settings for a gatherer: its faction's, if set, otherwise the global ones
+    pub fn flocking_for(&self, entity: &Entity) -> &FlockingConfig {
+        entity
+            .faction
+            .and_then(|id| self.factions.get(id))
+            .and_then(|faction| faction.flocking.as_ref())
+            .unwrap_or(&self.flocking)
+    }
+
+    /// Utility scoring for an entity type: the built-in profile with any
+    /// configured actions and hysteresis laid over it
+    pub fn utility_profile(&self, entity_type: EntityType) -> UtilityProfile {
+        let mut profile = UtilityProfile::for_entity_type(entity_type);
+        if let Some(overrides) = self.utility.get(entity_type.name()) {
+            profile.hysteresis = overrides.hysteresis;
+            for (action, scoring) in &overrides.actions {
+                profile.actions.insert(*action, scoring.clone());
+            }
+        }
+        profile
+    }
+}
+
+/// Set of species a world is populated with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// Gatherers, resources and predators
+    #[default]
+    Classic,
+    /// Plants, herbivores, mesopredators and an apex predator
+    FoodWeb,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 2] = [Preset::Classic, Preset::FoodWeb];
+
+    /// Name used in config files and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Classic => "classic",
+            Preset::FoodWeb => "food_web",
+        }
+    }
+
+    /// Look a preset up by name, accepting `-` for `_`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.replace('-', "_");
+        Self::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    /// Interaction rules between the preset's own species
+    pub fn interactions(&self) -> Vec<InteractionRule> {
+        match self {
+            Preset::Classic => Vec::new(),
+            Preset::FoodWeb => food_web::interactions(),
+        }
+    }
+}
+
+/// Boids-style herding for gatherers, blended with foraging and fleeing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlockingConfig {
+    /// Whether gatherers herd at all
+    pub enabled: bool,
+    /// Gatherers closer than this count as herd-mates
+    pub neighbour_radius: f32,
+    /// Personal space kept between gatherers
+    pub separation_radius: f32,
+    pub separation_weight: f32,
+    pub alignment_weight: f32,
+    pub cohesion_weight: f32,
+    pub forage_weight: f32,
+    /// How strongly fear outweighs hunger when blending the two
+    pub flee_weight: f32,
+    /// Fear felt for a predator that only a herd-mate can see, relative to
+    /// seeing it directly (many-eyes detection)
+    pub alarm_fear: f32,
+}
+
+impl Default for FlockingConfig {
+    fn default() -> Self {
+        FlockingConfig {
+            enabled: false,
+            neighbour_radius: 60.0,
+            separation_radius: 12.0,
+            separation_weight: 1.0,
+            alignment_weight: 0.3,
+            cohesion_weight: 0.2,
+            forage_weight: 1.0,
+            flee_weight: 1.5,
+            alarm_fear: 0.7,
+        }
+    }
+}
+
+/// Cooperative hunting among predators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackConfig {
+    /// Whether predators form packs at all
+    pub enabled: bool,
+    /// Lone predators this close to another predator team up
+    pub formation_radius: f32,
+    pub max_size: usize,
+    /// Distance from the target at which followers take up their flanking slots
+    pub encircle_radius: f32,
+    /// Members this close to a kill get an equal share of its energy
+    pub share_radius: f32,
+    /// Packs whose members have gone this long without food on average break up
+    pub starvation_break_time: f32,
+}
+
+impl Default for PackConfig {
+    fn default() -> Self {
+        PackConfig {
+            enabled: false,
+            formation_radius: 80.0,
+            max_size: 4,
+            encircle_radius: 45.0,
+            share_radius: 120.0,
+            starvation_break_time: 20.0,
+        }
+    }
+}
+
+/// Nests gatherers haul food back to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NestConfig {
+    /// Whether gatherers in the classic preset live in nests at all
+    pub enabled: bool,
+    /// Nests placed at the start of a run; gatherers join the nearest one
+    pub count: usize,
+    pub radius: f32,
+    /// Food energy a gatherer can carry home at once
+    pub carry_capacity: u32,
+    /// Members at home below this fraction of their maximum energy eat from the store
+    pub feed_below: f32,
+    /// Stored food needed to raise a new gatherer, which starts with that much
+    /// energy; anything beyond what it can hold goes back into the store
+    pub spawn_cost: u32,
+}
+
+impl Default for NestConfig {
+    fn default() -> Self {
+        NestConfig {
+            enabled: false,
+            count: 1,
+            radius: 18.0,
+            carry_capacity: 20,
+            feed_below: 0.6,
+            spawn_cost: 120,
+        }
+    }
+}
+
+/// Scent trails gatherers lay between food and their nest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PheromoneConfig {
+    /// Whether gatherers lay and follow trails
+    pub enabled: bool,
+    /// Side length in pixels of the cells trails are tracked in
+    pub cell_size: f32,
+    /// Scent laid per second by a gatherer at full strength
+    pub deposit: f32,
+    /// How much of its strength a gatherer's scent loses per second of walking
+    pub scent_fade: f32,
+    /// Fraction of every trail that evaporates per second
+    pub evaporation: f32,
+    /// Fraction of every cell that spreads to its neighbours per second
+    pub diffusion: f32,
+    /// Pull of a food trail on gatherers with no food in sight
+    pub follow_weight: f32,
+    /// Gradients weaker than this are ignored
+    pub min_gradient: f32,
+}
+
+impl Default for PheromoneConfig {
+    fn default() -> Self {
+        PheromoneConfig {
+            enabled: false,
+            cell_size: 10.0,
+            deposit: 1.0,
+            scent_fade: 0.05,
+            evaporation: 0.02,
+            diffusion: 0.5,
+            follow_weight: 1.0,
+            min_gradient: 0.005,
+        }
+    }
+}
+
+/// Territories scent-marked by predators and by faction gatherers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerritoryConfig {
+    /// Whether predators and factions claim ground
+    pub enabled: bool,
+    /// Side length in pixels of the cells territory is claimed in
+    pub cell_size: f32,
+    /// Claim strength a patrolling owner adds to its cell per second, or
+    /// wears off a rival's; a cell is held at full strength at 1
+    pub mark_rate: f32,
+    /// Claim strength every cell loses per second without patrol
+    pub decay_rate: f32,
+    /// Claims at least this strong are respected: predators don't hunt prey
+    /// standing in them and intruders pay the penalty
+    pub respect_above: f32,
+    /// Energy an intruder loses per second spent in a rival's territory
+    pub intruder_penalty: u32,
+}
+
+impl Default for TerritoryConfig {
+    fn default() -> Self {
+        TerritoryConfig {
+            enabled: false,
+            cell_size: 40.0,
+            mark_rate: 0.5,
+            decay_rate: 0.02,
+            respect_above: 0.3,
+            intruder_penalty: 2,
+        }
+    }
+}
+
+/// Index of a faction in the config's `factions` list
+pub type FactionId = usize;
+
+/// A colony of gatherers with its own nests, colours and behaviour settings,
+/// competing with the others for the same resources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FactionConfig {
+    pub name: String,
+    /// Colour of the faction's gatherers at full energy and of its nests
+    pub color: u32,
+    /// Gatherers at the start of a run
+    pub gatherers: usize,
+    /// Nests at the start of a run, if nests are enabled
+    pub nests: usize,
+    /// Top the faction up with two gatherers whenever it falls below three;
+    /// turn off to let a faction be driven extinct
+    pub reinforce: bool,
+    /// Multiplier on the normal gatherer speed
+    pub speed_factor: f32,
+    /// Energy a member knocks off a rival gatherer it touches, at most once a
+    /// second; 0 for a peaceful faction
+    pub aggression: u32,
+    /// Herding settings replacing the `[flocking]` section for this faction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flocking: Option<FlockingConfig>,
+    /// Brain replacing the gatherer brain for this faction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brain: Option<BrainSpec>,
+}
+
+impl Default for FactionConfig {
+    fn default() -> Self {
+        FactionConfig {
+            name: "colony".into(),
+            color: 0x00FF00,
+            gatherers: 10,
+            nests: 1,
+            reinforce: true,
+            speed_factor: 1.0,
+            aggression: 0,
+            flocking: None,
+            brain: None,
+        }
+    }
+}
+
+/// Neuroevolution settings for entities with neural brains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NeuralConfig {
+    /// Hidden-layer size of freshly created networks
+    pub hidden_neurons: usize,
+    /// Chance of each weight being perturbed when a child inherits a network
+    pub mutation_rate: f32,
+    /// Standard deviation of the perturbation
+    pub mutation_strength: f32,
+}
+
+impl Default for NeuralConfig {
+    fn default() -> Self {
+        NeuralConfig {
+            hidden_neurons: 8,
+            mutation_rate: 0.1,
+            mutation_strength: 0.3,
+        }
+    }
+}
+
+/// Rhai script with hooks into the simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptConfig {
+    /// Script file, relative to the config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Reload the script whenever the file changes
+    pub hot_reload: bool,
+    /// Operations one hook call may run before it is aborted
+    pub max_operations: u64,
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        ScriptConfig {
+            file: None,
+            hot_reload: true,
+            max_operations: 100_000,
+        }
+    }
+}
+
+/// Carcasses, scavengers and the soil fertility decomposition feeds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CarcassConfig {
+    /// Whether dead gatherers, predators and scavengers leave carcasses
+    pub enabled: bool,
+    /// Carcass energy per unit of the dead entity's size
+    pub energy_per_size: u32,
+    /// Energy a carcass loses to decomposition each second
+    pub decay_rate: u32,
+    /// Fraction of decomposed energy that ends up as soil fertility
+    pub decomposition_efficiency: f32,
+    /// Fertility a resource can draw from its patch of soil each second,
+    /// turned into energy on top of its normal regrowth
+    pub fertility_uptake: f32,
+    /// Side length in pixels of the patches soil fertility is tracked in
+    pub soil_cell_size: f32,
+    /// Scavengers at the start of a run
+    pub initial_scavengers: usize,
+}
+
+impl Default for CarcassConfig {
+    fn default() -> Self {
+        CarcassConfig {
+            enabled: false,
+            energy_per_size: 15,
+            decay_rate: 1,
+            decomposition_efficiency: 1.0,
+            fertility_uptake: 2.0,
+            soil_cell_size: 25.0,
+            initial_scavengers: 0,
+        }
+    }
+}
+
+/// An SEIR pathogen passed on between touching hosts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiseaseConfig {
+    /// Whether the disease is present at all
+    pub enabled: bool,
+    /// Entity types that can catch and pass on the disease
+    pub hosts: Vec<EntityType>,
+    /// Hosts infected at the start of a run
+    pub initial_infected: usize,
+    /// Infections per second of contact between an infectious and a susceptible host
+    pub transmission_rate: f32,
+    /// Seconds between catching the disease and becoming infectious; 0 skips
+    /// the exposed stage, turning the model from SEIR into SIR
+    pub incubation_period: f32,
+    /// Seconds a host stays infectious and ill
+    pub infectious_period: f32,
+    /// Seconds of immunity after recovery; `inf` for lifelong immunity
+    pub immunity_period: f32,
+    /// Energy an ill host loses per second
+    pub energy_drain: f32,
+    /// Fraction of their usual speed ill hosts can manage
+    pub speed_factor: f32,
+}
+
+impl Default for DiseaseConfig {
+    fn default() -> Self {
+        DiseaseConfig {
+            enabled: false,
+            hosts: vec![EntityType::Gatherer],
+            initial_infected: 2,
+            transmission_rate: 0.8,
+            incubation_period: 5.0,
+            infectious_period: 20.0,
+            immunity_period: 60.0,
+            energy_drain: 0.5,
+            speed_factor: 0.7,
+        }
+    }
+}
+
+/// Energy mobile entities burn living and moving. Every cost scales with
+/// body mass, taken as `size` raised to `size_exponent`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetabolismConfig {
+    /// Energy burned per second per unit of body mass just staying alive
+    pub basal_rate: f32,
+    /// How steeply body mass grows with size; 0.75 follows Kleiber's law
+    pub size_exponent: f32,
+    /// Energy burned per pixel moved per unit of body mass
+    pub movement_cost: f32,
+    /// Energy burned per second per unit of body mass at top speed, falling
+    /// off with the square of the fraction of top speed used
+    pub speed_cost: f32,
+    /// Entities slower than this many pixels per second are resting
+    pub rest_speed: f32,
+    /// Fraction of the basal rate burned while resting
+    pub rest_factor: f32,
+}
+
+impl MetabolismConfig {
+    /// Energy burned per second by an entity of a given size moving at `speed`
+    /// out of a top speed of `max_speed`, before its life stage's multiplier
+    pub fn rate(&self, size: u32, speed: f32, max_speed: f32) -> f32 {
+        let mass = (size as f32).powf(self.size_exponent);
+        let basal = if speed < self.rest_speed {
+            self.basal_rate * self.rest_factor
+        } else {
+            self.basal_rate
+        };
+        let effort = (speed / max_speed).powi(2);
+        (basal + self.movement_cost * speed + self.speed_cost * effort) * mass
+    }
+}
+
+impl Default for MetabolismConfig {
+    fn default() -> Self {
+        MetabolismConfig {
+            basal_rate: 0.12,
+            size_exponent: 0.75,
+            movement_cost: 0.002,
+            speed_cost: 0.1,
+            rest_speed: 3.0,
+            rest_factor: 0.5,
+        }
+    }
+}
+
+/// Life stages and natural lifespans of mobile entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifeConfig {
+    /// Whether mobile entities age through stages and die of old age
+    pub enabled: bool,
+    /// Fraction of its lifespan an entity spends as a juvenile
+    pub juvenile_until: f32,
+    /// Fraction of its lifespan after which an entity is an elder
+    pub elder_from: f32,
+    pub juvenile: StageConfig,
+    pub adult: StageConfig,
+    pub elder: StageConfig,
+    /// Lifespan bounds per entity type name
+    pub lifespans: BTreeMap<String, LifespanRange>,
+    /// Lifespan bounds for mobile types not listed in `lifespans`
+    pub default_lifespan: LifespanRange,
+}
+
+impl LifeConfig {
+    /// Lifespan bounds for an entity type
+    pub fn lifespan_of(&self, entity_type: EntityType) -> LifespanRange {
+        self.lifespans.get(entity_type.name()).copied().unwrap_or(self.default_lifespan)
+    }
+}
+
+impl Default for LifeConfig {
+    fn default() -> Self {
+        let lifespans = [
+            ("gatherer", 150.0, 250.0),
+            ("predator", 160.0, 200.0),
+            ("scavenger", 200.0, 300.0),
+            ("herbivore", 120.0, 200.0),
+            ("mesopredator", 160.0, 240.0),
+            ("apex", 200.0, 300.0),
+        ]
+        .into_iter()
+        .map(|(name, min, max)| (name.to_string(), LifespanRange { min, max }))
+        .collect();
+
+        LifeConfig {
+            enabled: false,
+            juvenile_until: 0.15,
+            elder_from: 0.8,
+            juvenile: StageConfig { speed: 0.7, metabolism: 0.7, fertility: 0.0 },
+            adult: StageConfig::default(),
+            elder: StageConfig { speed: 0.75, metabolism: 1.3, fertility: 0.3 },
+            lifespans,
+            default_lifespan: LifespanRange { min: 150.0, max: 250.0 },
+        }
+    }
+}
+
+/// How an entity behaves in one life stage, relative to an adult
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageConfig {
+    /// Multiplier on top speed
+    pub speed: f32,
+    /// Multiplier on the rate energy is burned
+    pub metabolism: f32,
+    /// How readily it breeds: 0 can't, 1 recovers from a birth at the adult
+    /// rate, lower values take proportionally longer
+    pub fertility: f32,
+}
+
+impl Default for StageConfig {
+    fn default() -> Self {
+        StageConfig { speed: 1.0, metabolism: 1.0, fertility: 1.0 }
+    }
+}
+
+/// Bounds, in seconds, a lifespan is drawn uniformly between
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LifespanRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Accounting of energy sources and sinks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergyConfig {
+    /// Whether to keep the energy ledger, which costs a scan of the world's
+    /// energy after every step of an update; `--energy-report` turns it on
+    pub audit: bool,
+    /// Panic as soon as a flow goes the wrong way, e.g. predation creating
+    /// energy, for use in tests
+    pub strict: bool,
+    /// Largest wrong-way flow per update put down to rounding
+    pub tolerance: f64,
+}
+
+impl EnergyConfig {
+    /// Whether the ledger is kept, as it is for strict mode too
+    pub fn audited(&self) -> bool {
+        self.audit || self.strict
+    }
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        EnergyConfig { audit: false, strict: false, tolerance: 0.01 }
+    }
+}
+
+/// Keyframes kept in the window for rewinding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimelineConfig {
+    /// Whether to keep keyframes and show the timeline
+    pub enabled: bool,
+    /// Updates between keyframes
+    pub keyframe_interval: u64,
+    /// Keyframes kept before the oldest is dropped
+    pub keyframes: usize,
+}
+
+impl Default for TimelineConfig {
+    fn default() -> Self {
+        TimelineConfig { enabled: true, keyframe_interval: 60, keyframes: 180 }
+    }
+}
+
+/// Time series recorded during a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Seconds of simulated time between samples
+    pub interval: f32,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        StatsConfig { interval: 1.0 }
+    }
+}
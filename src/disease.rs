@@ -0,0 +1,126 @@
+use crate::config::DiseaseConfig;
+use crate::entity::Entity;
+
+/// Where an entity is in the course of an infection
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HealthState {
+    /// Can catch the disease
+    #[default]
+    Susceptible,
+    /// Caught it, but not yet ill or infectious, for this many more seconds
+    Exposed { remaining: f32 },
+    /// Ill and infectious for this many more seconds; `drain` is energy lost
+    /// to the illness that hasn't added up to a whole unit yet
+    Infected { remaining: f32, drain: f32 },
+    /// Immune for this many more seconds
+    Recovered { remaining: f32 },
+}
+
+impl HealthState {
+    pub const NAMES: [&'static str; 4] = ["susceptible", "exposed", "infected", "recovered"];
+
+    /// Name of the state as used in statistics and scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            HealthState::Susceptible => "susceptible",
+            HealthState::Exposed { .. } => "exposed",
+            HealthState::Infected { .. } => "infected",
+            HealthState::Recovered { .. } => "recovered",
+        }
+    }
+
+    /// Whether the entity passes the disease on and suffers from it
+    pub fn is_infectious(&self) -> bool {
+        matches!(self, HealthState::Infected { .. })
+    }
+}
+
+/// State of a freshly infected entity: exposed, or straight to infected when
+/// there is no incubation period (SIR rather than SEIR)
+pub fn infection(settings: &DiseaseConfig) -> HealthState {
+    if settings.incubation_period > 0.0 {
+        HealthState::Exposed { remaining: settings.incubation_period }
+    } else {
+        HealthState::Infected { remaining: settings.infectious_period, drain: 0.0 }
+    }
+}
+
+/// Advance an entity's infection by one time step, draining its energy while ill
+pub fn progress(entity: &mut Entity, settings: &DiseaseConfig, delta_time: f32) {
+    entity.health = match entity.health {
+        HealthState::Susceptible => HealthState::Susceptible,
+        HealthState::Exposed { remaining } if remaining > delta_time => {
+            HealthState::Exposed { remaining: remaining - delta_time }
+        }
+        HealthState::Exposed { .. } => HealthState::Infected { remaining: settings.infectious_period, drain: 0.0 },
+        HealthState::Infected { remaining, drain } if remaining > delta_time => {
+            let mut drain = drain + settings.energy_drain * delta_time;
+            if drain >= 1.0 {
+                entity.energy = entity.energy.saturating_sub(drain.floor() as u32);
+                drain = drain.fract();
+            }
+            HealthState::Infected { remaining: remaining - delta_time, drain }
+        }
+        HealthState::Infected { .. } => HealthState::Recovered { remaining: settings.immunity_period },
+        HealthState::Recovered { remaining } if remaining > delta_time => {
+            HealthState::Recovered { remaining: remaining - delta_time }
+        }
+        HealthState::Recovered { .. } => HealthState::Susceptible,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    fn settings() -> DiseaseConfig {
+        DiseaseConfig {
+            incubation_period: 2.0,
+            infectious_period: 4.0,
+            immunity_period: 3.0,
+            energy_drain: 0.5,
+            ..DiseaseConfig::default()
+        }
+    }
+
+    /// Step an entity's infection a whole second at a time, returning the state
+    /// after each step
+    fn course(entity: &mut Entity, settings: &DiseaseConfig, seconds: usize) -> Vec<&'static str> {
+        (0..seconds)
+            .map(|_| {
+                progress(entity, settings, 1.0);
+                entity.health.name()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn infection_runs_through_every_stage_back_to_susceptible() {
+        let settings = settings();
+        let mut entity = Entity::new_gatherer(1, Position::new(0.0, 0.0));
+        assert_eq!(course(&mut entity, &settings, 3), ["susceptible"; 3]);
+
+        entity.health = infection(&settings);
+        let energy = entity.energy;
+        assert_eq!(
+            course(&mut entity, &settings, 9),
+            ["exposed", "infected", "infected", "infected", "infected", "recovered", "recovered", "recovered", "susceptible"]
+        );
+        // Four seconds ill at half a unit a second, with the last half unit
+        // still pending when the entity recovered
+        assert_eq!(entity.energy, energy - 1);
+    }
+
+    #[test]
+    fn without_incubation_or_immunity_expiry_the_model_is_sir() {
+        let settings = DiseaseConfig { incubation_period: 0.0, immunity_period: f32::INFINITY, ..settings() };
+        let mut entity = Entity::new_gatherer(1, Position::new(0.0, 0.0));
+        entity.health = infection(&settings);
+        assert!(entity.health.is_infectious());
+
+        let states = course(&mut entity, &settings, 100);
+        assert_eq!(states[3], "recovered");
+        assert_eq!(states[99], "recovered");
+    }
+}
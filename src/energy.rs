@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// A process that moves energy into or out of the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EnergyFlow {
+    /// Resources and plants regrowing
+    Photosynthesis,
+    /// Entities added by periodic replenishment
+    Spawning,
+    /// Bodies left where mobile entities die
+    Carcasses,
+    /// Mutualism rules rewarding both partners
+    Mutualism,
+    /// Changes made between updates, by scripts or the user
+    External,
+    /// Energy burned living and moving
+    Metabolism,
+    /// Energy drained by illness
+    Disease,
+    /// Energy lost between what is eaten and what the eater gains
+    Predation,
+    /// Competition rules costing both sides
+    Competition,
+    /// Blows between rival factions
+    Fighting,
+    /// Penalties paid by intruders in rival territory
+    Territory,
+    /// Energy lost splitting a parent's share off to a child
+    Reproduction,
+    /// Energy lost raising gatherers from nest stores
+    Nests,
+    /// Carcass energy that rots away without feeding the soil
+    Decomposition,
+    /// Energy left in entities when they die or are carried off with them
+    Death,
+    /// Running up metabolic bills and pushing entities out of obstacles, which
+    /// only move entities and should neither create nor destroy energy
+    Movement,
+}
+
+/// Which way a flow is allowed to go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    /// Only adds energy
+    Source,
+    /// Only removes energy
+    Sink,
+    /// Either way
+    Either,
+    /// Neither way
+    None,
+}
+
+impl EnergyFlow {
+    pub const ALL: [EnergyFlow; 16] = [
+        EnergyFlow::Photosynthesis,
+        EnergyFlow::Spawning,
+        EnergyFlow::Carcasses,
+        EnergyFlow::Mutualism,
+        EnergyFlow::External,
+        EnergyFlow::Metabolism,
+        EnergyFlow::Disease,
+        EnergyFlow::Predation,
+        EnergyFlow::Competition,
+        EnergyFlow::Fighting,
+        EnergyFlow::Territory,
+        EnergyFlow::Reproduction,
+        EnergyFlow::Nests,
+        EnergyFlow::Decomposition,
+        EnergyFlow::Death,
+        EnergyFlow::Movement,
+    ];
+
+    /// Name of the flow as used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnergyFlow::Photosynthesis => "photosynthesis",
+            EnergyFlow::Spawning => "spawning",
+            EnergyFlow::Carcasses => "carcasses",
+            EnergyFlow::Mutualism => "mutualism",
+            EnergyFlow::External => "external",
+            EnergyFlow::Metabolism => "metabolism",
+            EnergyFlow::Disease => "disease",
+            EnergyFlow::Predation => "predation",
+            EnergyFlow::Competition => "competition",
+            EnergyFlow::Fighting => "fighting",
+            EnergyFlow::Territory => "territory",
+            EnergyFlow::Reproduction => "reproduction",
+            EnergyFlow::Nests => "nests",
+            EnergyFlow::Decomposition => "decomposition",
+            EnergyFlow::Death => "death",
+            EnergyFlow::Movement => "movement",
+        }
+    }
+
+    /// Which way the flow should go in an ecologically sensible world
+    pub fn direction(&self) -> FlowDirection {
+        match self {
+            EnergyFlow::Photosynthesis | EnergyFlow::Spawning | EnergyFlow::Carcasses | EnergyFlow::Mutualism => {
+                FlowDirection::Source
+            }
+            EnergyFlow::External => FlowDirection::Either,
+            EnergyFlow::Movement => FlowDirection::None,
+            _ => FlowDirection::Sink,
+        }
+    }
+}
+
+/// Running account of where the world's energy comes from and goes.
+///
+/// The stock is all energy held by entities, carried to or stored in nests,
+/// and in the soil. Each update's change in the stock is split over the flows
+/// that caused it, so what is left unexplained by one step's detailed records
+/// is charged to that step as a whole.
+#[derive(Debug, Clone, Default)]
+pub struct EnergyLedger {
+    /// Flows during the latest update
+    tick: BTreeMap<EnergyFlow, f64>,
+    /// Flows since the start of the run
+    totals: BTreeMap<EnergyFlow, f64>,
+    /// Recorded since the last checkpoint, to be left out of the step's remainder
+    since_checkpoint: f64,
+    /// Stock at the end of the latest update
+    stock: Option<f64>,
+    /// Stock at the start of the run
+    initial_stock: f64,
+    elapsed: f32,
+}
+
+impl EnergyLedger {
+    /// Start an update, charging any change since the last one to `External`
+    pub fn begin_tick(&mut self, stock: f64) {
+        self.tick.clear();
+        match self.stock {
+            Some(previous) => self.record(EnergyFlow::External, stock - previous),
+            None => self.initial_stock = stock,
+        }
+        self.since_checkpoint = 0.0;
+    }
+
+    /// Add an amount to a flow, positive for energy entering the world
+    pub fn record(&mut self, flow: EnergyFlow, amount: f64) {
+        if amount == 0.0 {
+            return;
+        }
+        *self.tick.entry(flow).or_default() += amount;
+        *self.totals.entry(flow).or_default() += amount;
+        self.since_checkpoint += amount;
+    }
+
+    /// Close a step that moved the stock from `before` to `after`, charging
+    /// whatever its detailed records don't explain to `flow`
+    pub fn checkpoint(&mut self, flow: EnergyFlow, before: f64, after: f64) {
+        let remainder = after - before - self.since_checkpoint;
+        self.record(flow, remainder);
+        self.since_checkpoint = 0.0;
+    }
+
+    /// Finish an update
+    pub fn end_tick(&mut self, stock: f64, delta_time: f32) {
+        self.stock = Some(stock);
+        self.elapsed += delta_time;
+    }
+
+    /// Check the latest update against each flow's direction, describing the
+    /// first flow that went the wrong way by more than `tolerance`
+    pub fn check(&self, tolerance: f64) -> Result<(), String> {
+        for (flow, amount) in &self.tick {
+            let wrong = match flow.direction() {
+                FlowDirection::Source => *amount < -tolerance,
+                FlowDirection::Sink => *amount > tolerance,
+                FlowDirection::None => amount.abs() > tolerance,
+                FlowDirection::Either => false,
+            };
+            if wrong {
+                let verb = if *amount > 0.0 { "created" } else { "destroyed" };
+                return Err(format!("{} {} {:.3} energy", flow.name(), verb, amount.abs()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Flows during the latest update
+    pub fn last_tick(&self) -> &BTreeMap<EnergyFlow, f64> {
+        &self.tick
+    }
+
+    /// Flows since the start of the run
+    pub fn totals(&self) -> &BTreeMap<EnergyFlow, f64> {
+        &self.totals
+    }
+
+    /// Sources, sinks and the balance since the start of the run as a table,
+    /// with rates per second of simulated time, or a note that nothing was audited
+    pub fn report(&self) -> String {
+        if self.stock.is_none() {
+            return "Energy ledger is empty: no update was audited (set [energy] audit = true)\n".to_string();
+        }
+        let per_second = |amount: f64| amount / (self.elapsed as f64).max(f64::EPSILON);
+        let mut report = String::new();
+        let _ = writeln!(report, "Energy ledger over {:.1}s", self.elapsed);
+        let _ = writeln!(report, "{:<16}{:>14}{:>12}", "flow", "total", "per second");
+        for flow in EnergyFlow::ALL {
+            let Some(amount) = self.totals.get(&flow) else { continue };
+            let _ = writeln!(report, "{:<16}{:>14.1}{:>12.2}", flow.name(), amount, per_second(*amount));
+        }
+
+        let sources: f64 = self.totals.values().filter(|a| **a > 0.0).sum();
+        let sinks: f64 = self.totals.values().filter(|a| **a < 0.0).sum();
+        let stock = self.stock.unwrap_or(self.initial_stock);
+        let _ = writeln!(report, "{:<16}{:>14.1}{:>12.2}", "sources", sources, per_second(sources));
+        let _ = writeln!(report, "{:<16}{:>14.1}{:>12.2}", "sinks", sinks, per_second(sinks));
+        let _ = writeln!(report, "stock {:.1} -> {:.1} (unaccounted {:.3})", self.initial_stock, stock, stock - self.initial_stock - (sources + sinks));
+        report
+    }
+}
@@ -1,15 +1,116 @@
-use crate::position::Position;
-use rand::Rng;
+use crate::brain::Blackboard;
+use crate::config::FactionId;
+use crate::disease::HealthState;
+use crate::life::LifeStage;
+use crate::neural::NeuralNetwork;
+use crate::nest::NestId;
+use crate::obstacle::{self, Obstacle};
+use crate::pack::PackId;
+use crate::position::{Position, Vec2};
+use crate::utility::UtilityAction;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
 
 /// Unique identifier for entities
 pub type EntityId = u64;
 
+/// How much faster than their cruising speed entities run when fleeing
+pub const FLEE_SPEED_MULTIPLIER: f32 = 1.6;
+
+/// Seconds an entity must wait between reproducing
+const REPRODUCTION_COOLDOWN: f32 = 20.0;
+
+/// Nest members eat what they find themselves below this fraction of their
+/// maximum energy, and carry it home otherwise
+const HAULING_HUNGER: f32 = 0.5;
+
+/// Extra energy spent per pixel run while fleeing, on top of normal consumption
+const FLEE_ENERGY_COST: f32 = 0.015;
+
 /// Different types of entities in the simulation
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EntityType {
     Gatherer,
     Resource,
     Predator,
+    /// Remains of a dead gatherer, predator or scavenger, slowly decomposing
+    Carcass,
+    /// Feeds on carcasses
+    Scavenger,
+    /// A species added through the simulation's species registry, by name
+    Custom(&'static str),
+}
+
+impl EntityType {
+    /// Every type the simulation ships with
+    pub const BUILTIN: [EntityType; 5] = [
+        EntityType::Gatherer,
+        EntityType::Resource,
+        EntityType::Predator,
+        EntityType::Carcass,
+        EntityType::Scavenger,
+    ];
+
+    /// Name used for this type in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntityType::Gatherer => "gatherer",
+            EntityType::Resource => "resource",
+            EntityType::Predator => "predator",
+            EntityType::Carcass => "carcass",
+            EntityType::Scavenger => "scavenger",
+            EntityType::Custom(name) => name,
+        }
+    }
+
+    /// Look a type up by its config name; names that aren't built in refer to custom species
+    pub fn from_name(name: &str) -> Self {
+        EntityType::BUILTIN
+            .into_iter()
+            .find(|t| t.name() == name)
+            .unwrap_or_else(|| EntityType::Custom(intern(name)))
+    }
+
+    /// Whether this is one of the types the simulation ships with
+    pub fn is_builtin(&self) -> bool {
+        !matches!(self, EntityType::Custom(_))
+    }
+
+    /// Whether entities of this type never move on their own
+    pub fn is_static(&self) -> bool {
+        matches!(self, EntityType::Resource | EntityType::Carcass)
+    }
+
+    /// Whether dead entities of this type leave a carcass behind
+    pub fn leaves_carcass(&self) -> bool {
+        matches!(self, EntityType::Gatherer | EntityType::Predator | EntityType::Scavenger)
+    }
+}
+
+/// Keep one copy of each custom species name for the life of the program
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = names.get(name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(interned);
+    interned
+}
+
+impl Serialize for EntityType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(EntityType::from_name(&name))
+    }
 }
 
 /// Core entity structure
@@ -17,15 +118,46 @@ pub enum EntityType {
 pub struct Entity {
     pub id: EntityId,
     pub position: Position,
+    pub velocity: Vec2,
     pub energy: u32,
     pub entity_type: EntityType,
     pub color: u32,
     pub max_energy: u32,
-    pub speed: f32,
+    pub max_speed: f32,
+    pub max_acceleration: f32,
     pub size: u32,
     pub age: f32,
     pub energy_consumption_timer: f32,
     pub time_since_last_hunt: f32,
+    pub sensing_radius: f32,
+    pub wander_heading: f32,
+    pub exertion: f32,
+    pub fleeing: bool,
+    pub pack_id: Option<PackId>,
+    pub blackboard: Blackboard,
+    pub utility_action: Option<UtilityAction>,
+    pub reproduction_cooldown: f32,
+    pub offspring: u32,
+    pub network: Option<NeuralNetwork>,
+    /// Simulation time of the last interaction with each target type, for rule
+    /// cooldowns, and of the last fight with a rival faction under `fight`
+    pub last_interactions: BTreeMap<&'static str, f32>,
+    pub health: HealthState,
+    /// Colony the entity belongs to, if factions are configured
+    pub faction: Option<FactionId>,
+    /// Nest the entity hauls food back to
+    pub nest_id: Option<NestId>,
+    /// Food energy being carried home
+    pub carrying: u32,
+    pub carry_capacity: u32,
+    /// Strength of the trail being laid, from 1 on picking up food or leaving
+    /// the nest down to 0
+    pub scent: f32,
+    /// Age at which the entity dies of old age; 0 if it never does
+    pub lifespan: f32,
+    pub stage: LifeStage,
+    /// Multiplier on the rate energy is burned, set by the life stage
+    pub metabolism: f32,
 }
 
 impl Entity {
@@ -34,15 +166,37 @@ impl Entity {
         Entity {
             id,
             position,
+            velocity: Vec2::ZERO,
             energy: 150,
             entity_type: EntityType::Gatherer,
             color: 0x00FF00,
             max_energy: 200,
-            speed: 2.0,
+            max_speed: 50.0,
+            max_acceleration: 120.0,
             size: 3,
             age: 0.0,
             energy_consumption_timer: 0.0,
             time_since_last_hunt: 0.0,
+            sensing_radius: 120.0,
+            wander_heading: 0.0,
+            exertion: 0.0,
+            fleeing: false,
+            pack_id: None,
+            blackboard: Blackboard::new(),
+            utility_action: None,
+            reproduction_cooldown: 0.0,
+            offspring: 0,
+            network: None,
+            last_interactions: BTreeMap::new(),
+            health: HealthState::Susceptible,
+            faction: None,
+            nest_id: None,
+            carrying: 0,
+            carry_capacity: 0,
+            scent: 0.0,
+            lifespan: 0.0,
+            stage: LifeStage::Adult,
+            metabolism: 1.0,
         }
     }
 
@@ -51,15 +205,37 @@ impl Entity {
         Entity {
             id,
             position,
+            velocity: Vec2::ZERO,
             energy: 80,
             entity_type: EntityType::Resource,
             color: 0xFFFF00,
             max_energy: 80,
-            speed: 0.0,
+            max_speed: 0.0,
+            max_acceleration: 0.0,
             size: 2,
             age: 0.0,
             energy_consumption_timer: 0.0,
             time_since_last_hunt: 0.0,
+            sensing_radius: 0.0,
+            wander_heading: 0.0,
+            exertion: 0.0,
+            fleeing: false,
+            pack_id: None,
+            blackboard: Blackboard::new(),
+            utility_action: None,
+            reproduction_cooldown: 0.0,
+            offspring: 0,
+            network: None,
+            last_interactions: BTreeMap::new(),
+            health: HealthState::Susceptible,
+            faction: None,
+            nest_id: None,
+            carrying: 0,
+            carry_capacity: 0,
+            scent: 0.0,
+            lifespan: 0.0,
+            stage: LifeStage::Adult,
+            metabolism: 1.0,
         }
     }
 
@@ -68,47 +244,129 @@ impl Entity {
         Entity {
             id,
             position,
+            velocity: Vec2::ZERO,
             energy: 150,
             entity_type: EntityType::Predator,
             color: 0xFF0000,
             max_energy: 220,
-            speed: 4.5,
+            max_speed: 75.0,
+            max_acceleration: 150.0,
             size: 4,
             age: 0.0,
             energy_consumption_timer: 0.0,
             time_since_last_hunt: 0.0,
+            sensing_radius: 160.0,
+            wander_heading: 0.0,
+            exertion: 0.0,
+            fleeing: false,
+            pack_id: None,
+            blackboard: Blackboard::new(),
+            utility_action: None,
+            reproduction_cooldown: 0.0,
+            offspring: 0,
+            network: None,
+            last_interactions: BTreeMap::new(),
+            health: HealthState::Susceptible,
+            faction: None,
+            nest_id: None,
+            carrying: 0,
+            carry_capacity: 0,
+            scent: 0.0,
+            lifespan: 0.0,
+            stage: LifeStage::Adult,
+            metabolism: 1.0,
+        }
+    }
+
+    /// Create a carcass holding the given energy
+    pub fn new_carcass(id: EntityId, position: Position, energy: u32) -> Self {
+        Entity {
+            entity_type: EntityType::Carcass,
+            color: 0x805030,
+            energy,
+            max_energy: energy.max(1),
+            max_speed: 0.0,
+            max_acceleration: 0.0,
+            sensing_radius: 0.0,
+            ..Self::new_resource(id, position)
+        }
+    }
+
+    /// Create a new scavenger entity
+    pub fn new_scavenger(id: EntityId, position: Position) -> Self {
+        Entity {
+            entity_type: EntityType::Scavenger,
+            color: 0xA040FF,
+            energy: 120,
+            max_energy: 180,
+            max_speed: 55.0,
+            max_acceleration: 110.0,
+            size: 3,
+            sensing_radius: 200.0,
+            ..Self::new_gatherer(id, position)
+        }
+    }
+
+    /// Create a new entity of the given type with that type's starting stats
+    pub fn new_of_type(entity_type: EntityType, id: EntityId, position: Position) -> Self {
+        match entity_type {
+            EntityType::Gatherer => Self::new_gatherer(id, position),
+            EntityType::Resource => Self::new_resource(id, position),
+            EntityType::Predator => Self::new_predator(id, position),
+            EntityType::Carcass => Self::new_carcass(id, position, 50),
+            EntityType::Scavenger => Self::new_scavenger(id, position),
+            EntityType::Custom(_) => Self::new_custom(entity_type, id, position),
+        }
+    }
+
+    /// Create an entity of a custom species with neutral stats, for the
+    /// species to adjust in `Species::init`
+    pub fn new_custom(entity_type: EntityType, id: EntityId, position: Position) -> Self {
+        Entity {
+            entity_type,
+            color: 0xFFFFFF,
+            max_energy: 100,
+            energy: 100,
+            max_speed: 40.0,
+            max_acceleration: 100.0,
+            ..Self::new_gatherer(id, position)
         }
     }
 
     /// Update entity behavior
-    pub fn update(&mut self, delta_time: f32, world_width: usize, world_height: usize, rng: &mut impl Rng) {
+    pub fn update(&mut self, delta_time: f32) {
         self.age += delta_time;
         self.energy_consumption_timer += delta_time;
+        self.reproduction_cooldown = (self.reproduction_cooldown - delta_time).max(0.0);
         
         if self.entity_type == EntityType::Predator {
             self.time_since_last_hunt += delta_time;
         }
         
         match self.entity_type {
-            EntityType::Gatherer => self.update_gatherer(delta_time, world_width, world_height, rng),
+            EntityType::Gatherer => self.update_gatherer(delta_time),
             EntityType::Resource => self.update_resource(delta_time),
-            EntityType::Predator => self.update_predator(delta_time, world_width, world_height, rng),
+            EntityType::Predator => self.update_predator(delta_time),
+            EntityType::Carcass => self.update_carcass(),
+            EntityType::Scavenger => self.update_scavenger(),
+            // Custom species are updated by their `Species` implementation
+            EntityType::Custom(_) => {}
         }
     }
 
-    /// Gatherer behavior: random movement, energy consumption
-    fn update_gatherer(&mut self, _delta_time: f32, world_width: usize, world_height: usize, rng: &mut impl Rng) {
-        if self.energy_consumption_timer >= 2.0 {
-            if self.energy > 0 {
-                self.energy = self.energy.saturating_sub(1);
-            }
-            self.energy_consumption_timer = 0.0;
+    /// Pay the whole units of the energy the simulation has charged for
+    /// living and moving, keeping the fraction for later
+    pub fn burn_exertion(&mut self) {
+        if self.exertion >= 1.0 {
+            let cost = self.exertion.floor();
+            self.energy = self.energy.saturating_sub(cost as u32);
+            self.exertion -= cost;
         }
+    }
 
-        if rng.gen_bool(0.6) {
-            self.position.add_random_offset(self.speed as i32, rng);
-            self.position.clamp_to_bounds(world_width, world_height);
-        }
+    /// Gatherer behavior: energy consumption
+    fn update_gatherer(&mut self, _delta_time: f32) {
+        self.burn_exertion();
 
         let energy_ratio = self.energy as f32 / self.max_energy as f32;
         let green_intensity = (255.0 * energy_ratio) as u32;
@@ -129,40 +387,37 @@ impl Entity {
         self.color = (intensity << 16) | (intensity << 8);
     }
 
-    /// Predator behavior: hunt gatherers, more complex movement
-    fn update_predator(&mut self, _delta_time: f32, world_width: usize, world_height: usize, rng: &mut impl Rng) {
-        if self.energy_consumption_timer >= 3.0 {
-            let mut energy_loss = 1;
-            
-            if self.time_since_last_hunt > 25.0 {
-                energy_loss = 3;
-                println!("Predator {} is starving (no hunt for {:.1}s)", self.id, self.time_since_last_hunt);
-            } else if self.time_since_last_hunt > 18.0 {
-                energy_loss = 2;
-            }
-            
-            if self.energy > 0 {
-                self.energy = self.energy.saturating_sub(energy_loss);
-            }
-            self.energy_consumption_timer = 0.0;
-        }
-        
-        if self.age > 180.0 {
-            self.energy = 0;
-            println!("Predator {} died of old age at {:.1}s", self.id, self.age);
-            return;
-        }
+    /// Carcass behavior: fades as it is eaten or decomposes, which the
+    /// simulation handles because the decay feeds the soil
+    fn update_carcass(&mut self) {
+        let energy_ratio = self.energy as f32 / self.max_energy as f32;
+        let shade = 0.3 + 0.7 * energy_ratio;
+        let red = (128.0 * shade) as u32;
+        let green = (80.0 * shade) as u32;
+        let blue = (48.0 * shade) as u32;
+        self.color = (red << 16) | (green << 8) | blue;
+    }
 
-        if rng.gen_bool(0.7) {
-            self.position.add_random_offset((self.speed * 1.2) as i32, rng);
-            self.position.clamp_to_bounds(world_width, world_height);
-        }
+    /// Scavenger behavior: energy consumption
+    fn update_scavenger(&mut self) {
+        self.burn_exertion();
+
+        let energy_ratio = self.energy as f32 / self.max_energy as f32;
+        let intensity = 0.4 + 0.6 * energy_ratio;
+        let red = (160.0 * intensity) as u32;
+        let blue = (255.0 * intensity) as u32;
+        self.color = (red << 16) | (0x40 << 8) | blue;
+    }
+
+    /// Predator behavior: energy consumption, dimming when hungry
+    fn update_predator(&mut self, _delta_time: f32) {
+        self.burn_exertion();
 
         let energy_ratio = self.energy as f32 / self.max_energy as f32;
         let mut red_intensity = (255.0 * energy_ratio) as u32;
         
         if self.time_since_last_hunt > 18.0 {
-            red_intensity = red_intensity / 2;
+            red_intensity /= 2;
         }
         
         self.color = red_intensity << 16;
@@ -173,61 +428,129 @@ impl Entity {
         self.energy == 0
     }
 
+    /// Split off a child that takes half of this entity's energy and inherits
+    /// an unmutated copy of its network, if it has one.
+    ///
+    /// Returns `None` if the entity is still recovering from the last birth or
+    /// has too little energy to spare.
+    pub fn reproduce(&mut self, child_id: EntityId, child_position: Position) -> Option<Entity> {
+        if self.reproduction_cooldown > 0.0 || self.energy < self.max_energy / 2 {
+            return None;
+        }
+
+        let mut child = Entity::new_of_type(self.entity_type, child_id, child_position);
+        child.energy = self.energy / 2;
+        child.wander_heading = self.wander_heading + std::f32::consts::PI;
+        child.network = self.network.clone();
+        child.faction = self.faction;
+        child.nest_id = self.nest_id;
+        child.carry_capacity = self.carry_capacity;
+        self.energy -= child.energy;
+        self.offspring += 1;
+        self.reproduction_cooldown = REPRODUCTION_COOLDOWN;
+        Some(child)
+    }
+
+    /// Take in food energy, returning how much was eaten. Nest members that
+    /// aren't hungry carry what fits home and eat only the rest.
+    pub fn take_food(&mut self, amount: u32) -> u32 {
+        let hungry = (self.energy as f32) < self.max_energy as f32 * HAULING_HUNGER;
+        let mut eaten = amount;
+        if self.nest_id.is_some() && !hungry {
+            let loaded = amount.min(self.carry_capacity.saturating_sub(self.carrying));
+            self.carrying += loaded;
+            if loaded > 0 {
+                self.scent = 1.0;
+            }
+            eaten -= loaded;
+        }
+        let eaten = eaten.min(self.max_energy - self.energy);
+        self.energy += eaten;
+        eaten
+    }
+
+    /// Energy held in the body plus any food being carried
+    pub fn stored_energy(&self) -> u32 {
+        self.energy + self.carrying
+    }
+
+    /// Whether the entity has a full load to take home
+    pub fn fully_loaded(&self) -> bool {
+        self.carry_capacity > 0 && self.carrying >= self.carry_capacity
+    }
+
     /// Check if entity can interact with another entity
     pub fn can_interact_with(&self, other: &Entity) -> bool {
         let distance = self.position.distance_squared_to(&other.position);
-        let interaction_range = ((self.size + other.size) * 3) as i32;
+        let interaction_range = ((self.size + other.size) * 3) as f32;
         distance <= interaction_range * interaction_range
     }
 
-    /// Gatherer consumes a resource
-    pub fn consume_resource(&mut self, resource: &mut Entity) -> bool {
-        if self.entity_type == EntityType::Gatherer 
-            && resource.entity_type == EntityType::Resource 
-            && self.can_interact_with(resource)
-            && resource.energy > 0 {
-            
-            let energy_transfer = resource.energy.min(30);
-            resource.energy -= energy_transfer;
-            self.energy = (self.energy + energy_transfer).min(self.max_energy);
-            
-            println!("Gatherer {} consumed {} energy from resource {} (now has {} energy)", 
-                     self.id, energy_transfer, resource.id, self.energy);
-            true
-        } else {
-            false
-        }
-    }
-
-    /// Predator hunts a gatherer
-    pub fn hunt_gatherer(&mut self, gatherer: &mut Entity) -> bool {
-        if self.entity_type == EntityType::Predator 
-            && gatherer.entity_type == EntityType::Gatherer 
-            && self.can_interact_with(gatherer) {
-            
-            let energy_stolen = gatherer.energy.min(40);
-            gatherer.energy = gatherer.energy.saturating_sub(energy_stolen);
-            self.energy = (self.energy + energy_stolen / 2).min(self.max_energy);
-            
-            self.time_since_last_hunt = 0.0;
-            
-            println!("Predator {} hunted gatherer {} for {} energy", 
-                     self.id, gatherer.id, energy_stolen);
-            true
-        } else {
-            false
-        }
-    }
-
-    /// Find the closest entity of a specific type
+    /// Check if another entity is within sensing range and not hidden behind an obstacle
+    pub fn can_sense(&self, other: &Entity, obstacles: &[Obstacle]) -> bool {
+        self.position.distance_squared_to(&other.position) <= self.sensing_radius * self.sensing_radius
+            && obstacle::has_line_of_sight(obstacles, &self.position, &other.position)
+    }
+
+    /// Find the closest sensed entity of a specific type
     pub fn find_closest_entity<'a>(
         &self, 
         entities: &'a [Entity], 
-        target_type: EntityType
+        target_type: EntityType,
+        obstacles: &[Obstacle],
     ) -> Option<&'a Entity> {
         entities
             .iter()
             .filter(|e| e.entity_type == target_type && e.id != self.id)
-            .min_by_key(|e| self.position.distance_squared_to(&e.position))
+            .filter(|e| self.can_sense(e, obstacles))
+            .min_by(|a, b| {
+                self.position
+                    .distance_squared_to(&a.position)
+                    .total_cmp(&self.position.distance_squared_to(&b.position))
+            })
+    }
+
+    /// Predators this entity can currently sense
+    pub fn sensed_predators<'a>(&self, entities: &'a [Entity], obstacles: &[Obstacle]) -> Vec<&'a Entity> {
+        entities
+            .iter()
+            .filter(|e| e.entity_type == EntityType::Predator && e.id != self.id)
+            .filter(|e| self.can_sense(e, obstacles))
+            .collect()
+    }
+
+    /// Integrate a steering force into velocity and position.
+    ///
+    /// The force is clamped to the maximum acceleration and the resulting
+    /// velocity to `speed_limit`. Returns the distance travelled.
+    pub fn apply_steering(&mut self, force: Vec2, speed_limit: f32, delta_time: f32) -> f32 {
+        let acceleration = force.truncated(self.max_acceleration);
+        self.velocity = (self.velocity + acceleration * delta_time).truncated(speed_limit);
+
+        let displacement = self.velocity * delta_time;
+        self.position.translate(displacement);
+
+        let distance = displacement.length();
+        if self.fleeing {
+            self.exertion += distance * FLEE_ENERGY_COST;
+        }
+        distance
     }
-} 
\ No newline at end of file
+
+    /// Keep the entity inside the world, cancelling velocity into the edge
+    pub fn bounce_off_bounds(&mut self, world_width: usize, world_height: usize) {
+        if self.position.is_within_bounds(world_width, world_height) {
+            return;
+        }
+
+        let before = self.position;
+        self.position.clamp_to_bounds(world_width, world_height);
+        if before.x != self.position.x {
+            self.velocity.x = -self.velocity.x * 0.5;
+        }
+        if before.y != self.position.y {
+            self.velocity.y = -self.velocity.y * 0.5;
+        }
+        self.wander_heading = self.velocity.angle();
+    }
+}
@@ -0,0 +1,299 @@
+use crate::brain::{SteeringPlan, WorldView};
+use crate::entity::{Entity, EntityType};
+use crate::interactions::{InteractionKind, InteractionRule};
+use crate::position::Vec2;
+use crate::species::Species;
+use crate::steering;
+
+/// Bottom of the food web: static, regrowing and spreading by seed
+pub const PLANT: EntityType = EntityType::Custom("plant");
+/// Small grazers living on plants
+pub const HERBIVORE: EntityType = EntityType::Custom("herbivore");
+/// Mid-level hunters of herbivores, themselves prey to the apex
+pub const MESOPREDATOR: EntityType = EntityType::Custom("mesopredator");
+/// Top of the food web, hunting mesopredators first and herbivores when it must
+pub const APEX: EntityType = EntityType::Custom("apex");
+
+/// How much faster than cruising speed animals sprint from a threat; less
+/// than the classic gatherers' burst, so chases down the web can end in a catch
+const FLIGHT_SPEED_MULTIPLIER: f32 = 1.25;
+
+/// Distance at which grazers start braking when arriving at a plant
+const ARRIVE_SLOWING_RADIUS: f32 = 20.0;
+
+/// Plants don't seed into ground already holding this many plants nearby
+const PLANT_CROWDING: usize = 3;
+const PLANT_CROWDING_RADIUS: f32 = 40.0;
+
+/// Who eats whom in the food web preset
+pub fn interactions() -> Vec<InteractionRule> {
+    vec![
+        InteractionRule { cooldown: 1.0, ..InteractionRule::new(HERBIVORE, PLANT, InteractionKind::Consume, 15, 1.0) },
+        InteractionRule::new(MESOPREDATOR, HERBIVORE, InteractionKind::Hunt, 40, 0.6),
+        InteractionRule::new(APEX, MESOPREDATOR, InteractionKind::Hunt, 60, 0.6),
+        InteractionRule::new(APEX, HERBIVORE, InteractionKind::Hunt, 30, 0.5),
+    ]
+}
+
+/// Scale a colour's brightness with the entity's energy, never below 30%
+fn shade(color: u32, entity: &Entity) -> u32 {
+    let brightness = 0.3 + 0.7 * entity.energy as f32 / entity.max_energy as f32;
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * brightness) as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Run from the nearest sensed threat, otherwise chase the first kind of food
+/// in order of preference that can be sensed, otherwise wander. Animals try to
+/// reproduce once their energy reaches `breed_at` of the maximum.
+fn forage(entity: &Entity, world: &WorldView, food: &[EntityType], threats: &[EntityType], breed_at: f32) -> SteeringPlan {
+    let threat = world
+        .entities
+        .iter()
+        .filter(|e| threats.contains(&e.entity_type))
+        .filter(|e| entity.can_sense(e, world.obstacles))
+        .min_by(|a, b| {
+            entity
+                .position
+                .distance_squared_to(&a.position)
+                .total_cmp(&entity.position.distance_squared_to(&b.position))
+        });
+    if let Some(threat) = threat {
+        return SteeringPlan {
+            force: steering::evade(entity, threat),
+            wander: false,
+            speed_limit: entity.max_speed * FLIGHT_SPEED_MULTIPLIER,
+            fleeing: true,
+            reproduce: false,
+        };
+    }
+
+    let target = food
+        .iter()
+        .find_map(|&food_type| entity.find_closest_entity(world.entities, food_type, world.obstacles));
+    let mut plan = match target {
+        Some(target) if target.max_speed > 0.0 => SteeringPlan::steer(steering::pursue(entity, target), entity.max_speed),
+        Some(target) => SteeringPlan::steer(
+            steering::arrive(entity, target.position, ARRIVE_SLOWING_RADIUS),
+            entity.max_speed * 0.7,
+        ),
+        None => SteeringPlan::wander(Vec2::ZERO, entity.max_speed * 0.5),
+    };
+    plan.reproduce = entity.energy as f32 >= entity.max_energy as f32 * breed_at;
+    plan
+}
+
+/// Plants regrow slowly, draw on soil fertility and seed into open ground when full
+pub struct Plant;
+
+impl Species for Plant {
+    fn name(&self) -> &'static str {
+        PLANT.name()
+    }
+
+    fn init(&self, entity: &mut Entity) {
+        entity.color = 0x30A030;
+        entity.energy = 40;
+        entity.max_energy = 60;
+        entity.max_speed = 0.0;
+        entity.max_acceleration = 0.0;
+        entity.size = 2;
+        entity.sensing_radius = 0.0;
+    }
+
+    fn initial_population(&self) -> usize {
+        40
+    }
+
+    fn update(&self, entity: &mut Entity, _delta_time: f32) {
+        if entity.energy_consumption_timer >= 1.0 {
+            entity.energy = (entity.energy + 1).min(entity.max_energy);
+            entity.energy_consumption_timer = 0.0;
+        }
+        entity.color = shade(0x30A030, entity);
+    }
+
+    fn plan(&self, entity: &Entity, world: &WorldView) -> Option<SteeringPlan> {
+        if entity.reproduction_cooldown > 0.0 || entity.energy < entity.max_energy {
+            return None;
+        }
+        let neighbours = world
+            .entities
+            .iter()
+            .filter(|e| e.entity_type == PLANT && e.id != entity.id)
+            .filter(|e| e.position.distance_to(&entity.position) <= PLANT_CROWDING_RADIUS)
+            .count();
+        if neighbours >= PLANT_CROWDING {
+            return None;
+        }
+        Some(SteeringPlan { reproduce: true, ..SteeringPlan::steer(Vec2::ZERO, 0.0) })
+    }
+
+    fn draws_on_soil(&self) -> bool {
+        true
+    }
+}
+
+/// Herbivores graze plants and run from both kinds of predator
+pub struct Herbivore;
+
+impl Species for Herbivore {
+    fn name(&self) -> &'static str {
+        HERBIVORE.name()
+    }
+
+    fn init(&self, entity: &mut Entity) {
+        entity.color = 0xE0E0A0;
+        entity.energy = 80;
+        entity.max_energy = 120;
+        entity.max_speed = 50.0;
+        entity.max_acceleration = 120.0;
+        entity.size = 3;
+        entity.sensing_radius = 90.0;
+    }
+
+    fn initial_population(&self) -> usize {
+        16
+    }
+
+    fn update(&self, entity: &mut Entity, _delta_time: f32) {
+        entity.burn_exertion();
+        entity.color = shade(0xE0E0A0, entity);
+    }
+
+    fn plan(&self, entity: &Entity, world: &WorldView) -> Option<SteeringPlan> {
+        Some(forage(entity, world, &[PLANT], &[MESOPREDATOR, APEX], 0.9))
+    }
+
+    fn leaves_carcass(&self) -> bool {
+        true
+    }
+}
+
+/// Mesopredators hunt herbivores and keep away from the apex predator
+pub struct Mesopredator;
+
+impl Species for Mesopredator {
+    fn name(&self) -> &'static str {
+        MESOPREDATOR.name()
+    }
+
+    fn init(&self, entity: &mut Entity) {
+        entity.color = 0xFF8000;
+        entity.energy = 120;
+        entity.max_energy = 180;
+        entity.max_speed = 62.0;
+        entity.max_acceleration = 130.0;
+        entity.size = 3;
+        entity.sensing_radius = 130.0;
+    }
+
+    fn initial_population(&self) -> usize {
+        5
+    }
+
+    fn update(&self, entity: &mut Entity, delta_time: f32) {
+        entity.time_since_last_hunt += delta_time;
+        entity.burn_exertion();
+        entity.color = shade(0xFF8000, entity);
+    }
+
+    fn plan(&self, entity: &Entity, world: &WorldView) -> Option<SteeringPlan> {
+        Some(forage(entity, world, &[HERBIVORE], &[APEX], 0.9))
+    }
+
+    fn leaves_carcass(&self) -> bool {
+        true
+    }
+}
+
+/// The apex predator fears nothing and prefers mesopredators to herbivores
+pub struct Apex;
+
+impl Species for Apex {
+    fn name(&self) -> &'static str {
+        APEX.name()
+    }
+
+    fn init(&self, entity: &mut Entity) {
+        entity.color = 0xFF2060;
+        entity.energy = 250;
+        entity.max_energy = 350;
+        entity.max_speed = 70.0;
+        entity.max_acceleration = 150.0;
+        entity.size = 5;
+        entity.sensing_radius = 180.0;
+    }
+
+    fn initial_population(&self) -> usize {
+        2
+    }
+
+    fn update(&self, entity: &mut Entity, delta_time: f32) {
+        entity.time_since_last_hunt += delta_time;
+        entity.burn_exertion();
+        entity.color = shade(0xFF2060, entity);
+    }
+
+    fn plan(&self, entity: &Entity, world: &WorldView) -> Option<SteeringPlan> {
+        Some(forage(entity, world, &[MESOPREDATOR, HERBIVORE], &[], 0.95))
+    }
+
+    fn leaves_carcass(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Preset, SimulationConfig};
+    use crate::position::Position;
+    use crate::simulation::Simulation;
+
+    fn food_web(seed: u64) -> Simulation {
+        let config = SimulationConfig { preset: Preset::FoodWeb, quiet: true, ..SimulationConfig::default() };
+        Simulation::with_seed(800, 600, config, seed)
+    }
+
+    fn count(simulation: &Simulation, entity_type: EntityType) -> usize {
+        simulation.get_entities().iter().filter(|e| e.entity_type == entity_type).count()
+    }
+
+    #[test]
+    fn preset_replaces_the_classic_colony_with_four_levels() {
+        let simulation = food_web(1);
+        assert_eq!(count(&simulation, PLANT), Plant.initial_population());
+        assert_eq!(count(&simulation, HERBIVORE), Herbivore.initial_population());
+        assert_eq!(count(&simulation, MESOPREDATOR), Mesopredator.initial_population());
+        assert_eq!(count(&simulation, APEX), Apex.initial_population());
+        for classic in [EntityType::Gatherer, EntityType::Resource, EntityType::Predator] {
+            assert_eq!(count(&simulation, classic), 0);
+        }
+
+        let table = simulation.config().interaction_table();
+        assert_eq!(table.get(HERBIVORE, PLANT).map(|rule| rule.kind), Some(InteractionKind::Consume));
+        assert_eq!(table.get(APEX, MESOPREDATOR).map(|rule| rule.kind), Some(InteractionKind::Hunt));
+        assert_eq!(table.get(MESOPREDATOR, APEX), None);
+    }
+
+    #[test]
+    fn herbivores_graze_plants_once_per_cooldown() {
+        let mut simulation = food_web(2);
+        let ids: Vec<_> = simulation.get_entities().iter().map(|e| e.id).collect();
+        for id in ids {
+            simulation.remove_entity(id);
+        }
+        let plant = simulation.spawn_entity(PLANT, Position::new(400.0, 300.0));
+        let herbivore = simulation.spawn_entity(HERBIVORE, Position::new(400.0, 300.0));
+        let hungry = simulation.entity_by_id(herbivore).unwrap().energy;
+
+        for _ in 0..30 {
+            simulation.update(1.0 / 60.0);
+        }
+
+        // One bite of 15 in half a second, with the plant regrowing at most a point
+        let plant_energy = simulation.entity_by_id(plant).unwrap().energy;
+        assert!((25..=26).contains(&plant_energy), "plant has {}", plant_energy);
+        assert!(simulation.entity_by_id(herbivore).unwrap().energy > hungry);
+    }
+}
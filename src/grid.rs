@@ -0,0 +1,144 @@
+use crate::position::{Position, Vec2};
+
+/// A coarse grid of values laid over the world, such as soil fertility
+#[derive(Debug, Clone)]
+pub struct ScalarGrid {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    values: Vec<f32>,
+}
+
+impl ScalarGrid {
+    /// Create a grid of zeros covering a world, with square cells of `cell_size` pixels
+    pub fn new(world_width: usize, world_height: usize, cell_size: f32) -> Self {
+        let cell_size = cell_size.max(1.0);
+        let columns = (world_width as f32 / cell_size).ceil().max(1.0) as usize;
+        let rows = (world_height as f32 / cell_size).ceil().max(1.0) as usize;
+        ScalarGrid { cell_size, columns, rows, values: vec![0.0; columns * rows] }
+    }
+
+    /// Column and row of the cell containing a position, clamped to the grid
+    fn cell_of(&self, position: &Position) -> (usize, usize) {
+        let column = ((position.x / self.cell_size).max(0.0) as usize).min(self.columns - 1);
+        let row = ((position.y / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        (column, row)
+    }
+
+    /// Value of the cell containing a position
+    pub fn get(&self, position: &Position) -> f32 {
+        let (column, row) = self.cell_of(position);
+        self.values[row * self.columns + column]
+    }
+
+    /// Add to the cell containing a position
+    pub fn add(&mut self, position: &Position, amount: f32) {
+        let (column, row) = self.cell_of(position);
+        self.values[row * self.columns + column] += amount;
+    }
+
+    /// Add an amount split evenly over the cell containing a position and its
+    /// neighbours
+    pub fn spread(&mut self, position: &Position, amount: f32) {
+        let (column, row) = self.cell_of(position);
+        let columns = column.saturating_sub(1)..=(column + 1).min(self.columns - 1);
+        let rows = row.saturating_sub(1)..=(row + 1).min(self.rows - 1);
+        let share = amount / (columns.clone().count() * rows.clone().count()) as f32;
+        for r in rows {
+            for c in columns.clone() {
+                self.values[r * self.columns + c] += share;
+            }
+        }
+    }
+
+    /// Remove up to `max` from the cell containing a position, returning how much was taken
+    pub fn take(&mut self, position: &Position, max: f32) -> f32 {
+        let (column, row) = self.cell_of(position);
+        let value = &mut self.values[row * self.columns + column];
+        let taken = value.min(max).max(0.0);
+        *value -= taken;
+        taken
+    }
+
+    /// Multiply every cell by a factor, e.g. to let a scent evaporate
+    pub fn scale(&mut self, factor: f32) {
+        for value in &mut self.values {
+            *value *= factor;
+        }
+    }
+
+    /// Move a fraction of every cell's value evenly to its neighbours
+    pub fn diffuse(&mut self, rate: f32) {
+        let rate = rate.clamp(0.0, 1.0);
+        if rate == 0.0 {
+            return;
+        }
+
+        let mut next = self.values.clone();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let index = row * self.columns + column;
+                let moving = self.values[index] * rate;
+                if moving == 0.0 {
+                    continue;
+                }
+                let neighbours = self.neighbours(column, row);
+                let share = moving / neighbours.len() as f32;
+                next[index] -= moving;
+                for neighbour in neighbours {
+                    next[neighbour] += share;
+                }
+            }
+        }
+        self.values = next;
+    }
+
+    /// Indices of the cells beside a cell, without wrapping round the edges
+    fn neighbours(&self, column: usize, row: usize) -> Vec<usize> {
+        let mut neighbours = Vec::with_capacity(4);
+        if column > 0 {
+            neighbours.push(row * self.columns + column - 1);
+        }
+        if column + 1 < self.columns {
+            neighbours.push(row * self.columns + column + 1);
+        }
+        if row > 0 {
+            neighbours.push((row - 1) * self.columns + column);
+        }
+        if row + 1 < self.rows {
+            neighbours.push((row + 1) * self.columns + column);
+        }
+        neighbours
+    }
+
+    /// Direction in which values rise fastest around a position, scaled by
+    /// how steeply, from the cells either side of it
+    pub fn gradient(&self, position: &Position) -> Vec2 {
+        let (column, row) = self.cell_of(position);
+        let value = |c: usize, r: usize| self.values[r * self.columns + c];
+        let left = column.saturating_sub(1);
+        let right = (column + 1).min(self.columns - 1);
+        let up = row.saturating_sub(1);
+        let down = (row + 1).min(self.rows - 1);
+        Vec2::new(value(right, row) - value(left, row), value(column, down) - value(column, up))
+    }
+
+    /// Sum of all cells
+    pub fn total(&self) -> f32 {
+        self.values.iter().sum()
+    }
+
+    /// Side length of a cell in pixels
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Every cell as its top-left corner in pixels and its value
+    pub fn cells(&self) -> impl Iterator<Item = (Position, f32)> + '_ {
+        self.values.iter().enumerate().map(|(index, value)| {
+            let column = index % self.columns;
+            let row = index / self.columns;
+            (Position::new(column as f32 * self.cell_size, row as f32 * self.cell_size), *value)
+        })
+    }
+}
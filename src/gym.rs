@@ -0,0 +1,203 @@
+use crate::brain::{self, SteeringPlan, WorldView, SENSOR_COUNT};
+use crate::config::SimulationConfig;
+use crate::entity::{EntityId, EntityType};
+use crate::position::Vec2;
+use crate::simulation::Simulation;
+use serde::{Deserialize, Serialize};
+
+/// Observation length: the standard sensors plus the agent's position
+pub const OBSERVATION_SIZE: usize = SENSOR_COUNT + 2;
+
+/// Number of discrete actions: stand still plus eight compass directions
+pub const DISCRETE_ACTIONS: usize = 9;
+
+/// What the agent sees each step, every value roughly in -1..1
+pub type Observation = [f32; OBSERVATION_SIZE];
+
+/// An action taken by the agent for one step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AgentAction {
+    /// 0 stands still; 1-8 run at full speed east, south-east, south and so on
+    /// clockwise round the compass
+    Discrete(usize),
+    /// Desired velocity as a fraction of maximum speed, clamped to length 1
+    Continuous { x: f32, y: f32 },
+}
+
+impl AgentAction {
+    /// Direction to run in, scaled by the fraction of top speed, or why the
+    /// action is invalid: a discrete action out of range or a non-finite velocity
+    pub fn direction(&self) -> Result<Vec2, String> {
+        match *self {
+            AgentAction::Discrete(0) => Ok(Vec2::ZERO),
+            AgentAction::Discrete(n) if n < DISCRETE_ACTIONS => {
+                Ok(Vec2::from_angle((n - 1) as f32 * std::f32::consts::TAU / (DISCRETE_ACTIONS - 1) as f32))
+            }
+            AgentAction::Discrete(n) => {
+                Err(format!("Discrete action {} out of range (expected 0..{})", n, DISCRETE_ACTIONS))
+            }
+            AgentAction::Continuous { x, y } if x.is_finite() && y.is_finite() => Ok(Vec2::new(x, y).truncated(1.0)),
+            AgentAction::Continuous { x, y } => Err(format!("Continuous action ({}, {}) is not finite", x, y)),
+        }
+    }
+}
+
+/// Settings for a training environment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvConfig {
+    /// Type of the entity the agent controls
+    pub agent_type: EntityType,
+    pub world_width: usize,
+    pub world_height: usize,
+    /// Simulation time advanced by each update
+    pub time_step: f32,
+    /// Updates run per step with the same action
+    pub frame_skip: u32,
+    /// Steps after which an episode is cut off
+    pub max_steps: u32,
+    /// Reward for every step survived
+    pub survival_reward: f32,
+    /// Reward subtracted when the agent dies
+    pub death_penalty: f32,
+    /// Settings of the simulated world; quiet by default, so episodes don't
+    /// print every hunt and birth
+    pub simulation: SimulationConfig,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            agent_type: EntityType::Gatherer,
+            world_width: 800,
+            world_height: 600,
+            time_step: 1.0 / 60.0,
+            frame_skip: 4,
+            max_steps: 3000,
+            survival_reward: 0.01,
+            death_penalty: 1.0,
+            simulation: SimulationConfig { quiet: true, ..SimulationConfig::default() },
+        }
+    }
+}
+
+/// Outcome of one step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepResult {
+    pub observation: Observation,
+    pub reward: f32,
+    /// The agent died; the episode is over
+    pub terminated: bool,
+    /// The episode hit `max_steps` with the agent still alive
+    pub truncated: bool,
+}
+
+/// A colony with one externally controlled entity, driven one step at a time.
+///
+/// Runs headless on fixed time steps, so an episode is fully determined by the
+/// seed passed to `reset` and the actions taken.
+pub struct ColonyEnv {
+    settings: EnvConfig,
+    simulation: Simulation,
+    agent: EntityId,
+    steps: u32,
+    last_energy: u32,
+    done: bool,
+}
+
+impl ColonyEnv {
+    /// Create an environment and start its first episode
+    pub fn new(settings: EnvConfig, seed: u64) -> Self {
+        let (simulation, agent) = Self::start(&settings, seed);
+        let last_energy = simulation.entity_by_id(agent).map_or(0, |e| e.energy);
+        ColonyEnv { settings, simulation, agent, steps: 0, last_energy, done: false }
+    }
+
+    fn start(settings: &EnvConfig, seed: u64) -> (Simulation, EntityId) {
+        let mut simulation =
+            Simulation::with_seed(settings.world_width, settings.world_height, settings.simulation.clone(), seed);
+        let agent = simulation.add_controlled_entity(settings.agent_type);
+        (simulation, agent)
+    }
+
+    /// Start a fresh episode from a seed
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        *self = Self::new(self.settings.clone(), seed);
+        self.observe()
+    }
+
+    /// Apply an action for `frame_skip` updates and report what happened.
+    ///
+    /// The reward is the change in the agent's energy as a fraction of its
+    /// maximum, plus `survival_reward`, minus `death_penalty` if it died.
+    /// Stepping a finished episode does nothing until `reset`. An invalid
+    /// action is an error and leaves the episode where it was.
+    pub fn step(&mut self, action: AgentAction) -> Result<StepResult, String> {
+        let direction = action.direction()?;
+        if self.done {
+            return Ok(StepResult { observation: self.observe(), reward: 0.0, terminated: true, truncated: false });
+        }
+
+        let Some(agent) = self.simulation.entity_by_id(self.agent) else {
+            self.done = true;
+            return Ok(StepResult { observation: self.observe(), reward: 0.0, terminated: true, truncated: false });
+        };
+        let max_speed = agent.max_speed;
+        let max_energy = agent.max_energy as f32;
+        let desired = direction * max_speed;
+
+        for _ in 0..self.settings.frame_skip.max(1) {
+            let Some(agent) = self.simulation.entity_by_id(self.agent) else { break };
+            let plan = SteeringPlan::steer(desired - agent.velocity, max_speed);
+            self.simulation.set_control(self.agent, plan);
+            self.simulation.update(self.settings.time_step);
+        }
+        self.steps += 1;
+
+        let energy = self.simulation.entity_by_id(self.agent).map(|e| e.energy);
+        let terminated = energy.is_none();
+        let truncated = !terminated && self.steps >= self.settings.max_steps;
+        self.done = terminated || truncated;
+
+        let energy = energy.unwrap_or(0);
+        let mut reward = (energy as f32 - self.last_energy as f32) / max_energy + self.settings.survival_reward;
+        if terminated {
+            reward -= self.settings.death_penalty;
+        }
+        self.last_energy = energy;
+
+        Ok(StepResult { observation: self.observe(), reward, terminated, truncated })
+    }
+
+    /// Sense the world from the agent's point of view; all zeros once it is dead
+    pub fn observe(&self) -> Observation {
+        let mut observation = [0.0; OBSERVATION_SIZE];
+        let Some(agent) = self.simulation.entity_by_id(self.agent) else { return observation };
+
+        let world = WorldView {
+            entities: self.simulation.get_entities(),
+            obstacles: self.simulation.get_obstacles(),
+            time: self.simulation.time(),
+        };
+        let (width, height) = self.simulation.world_size();
+        observation[..SENSOR_COUNT].copy_from_slice(&brain::sense(agent, &world));
+        observation[SENSOR_COUNT] = agent.position.x / width as f32 * 2.0 - 1.0;
+        observation[SENSOR_COUNT + 1] = agent.position.y / height as f32 * 2.0 - 1.0;
+        observation
+    }
+
+    /// Id of the entity the agent controls
+    pub fn agent_id(&self) -> EntityId {
+        self.agent
+    }
+
+    /// Steps taken in the current episode
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// The underlying simulation, e.g. for rendering or statistics
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+}
@@ -0,0 +1,234 @@
+use crate::entity::{Entity, EntityType};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// What happens when one entity touches another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionKind {
+    /// The actor eats from the target
+    Consume,
+    /// The actor bites the target; counts as a hunt for hunger and pack kill sharing,
+    /// and each actor gets one bite per interaction pass
+    Hunt,
+    /// Both lose energy fighting over the same space
+    Compete,
+    /// Both gain energy from the contact
+    Mutualism,
+    /// Nothing happens; used to switch off a built-in rule
+    None,
+    /// Something a custom species did, named by the species; not available in config
+    Other(&'static str),
+}
+
+impl InteractionKind {
+    /// Name of the interaction as reported to scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            InteractionKind::Consume => "consume",
+            InteractionKind::Hunt => "hunt",
+            InteractionKind::Compete => "compete",
+            InteractionKind::Mutualism => "mutualism",
+            InteractionKind::None => "none",
+            InteractionKind::Other(name) => name,
+        }
+    }
+}
+
+impl Serialize for InteractionKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for InteractionKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const KINDS: [InteractionKind; 5] = [
+            InteractionKind::Consume,
+            InteractionKind::Hunt,
+            InteractionKind::Compete,
+            InteractionKind::Mutualism,
+            InteractionKind::None,
+        ];
+        let name = String::deserialize(deserializer)?;
+        KINDS
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| D::Error::unknown_variant(&name, &["consume", "hunt", "compete", "mutualism", "none"]))
+    }
+}
+
+/// One entry of the interaction table: what an actor type does to a target type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InteractionRule {
+    pub actor: EntityType,
+    pub target: EntityType,
+    pub kind: InteractionKind,
+    /// Energy taken from the target (consume, hunt), lost by each side
+    /// (compete) or given to each side (mutualism) per contact
+    #[serde(default)]
+    pub amount: u32,
+    /// Fraction of the energy taken that the actor keeps, or of `amount` that
+    /// each side gains from mutualism
+    #[serde(default = "default_efficiency")]
+    pub efficiency: f32,
+    /// Seconds before the actor can do this to a target of the same type again
+    #[serde(default)]
+    pub cooldown: f32,
+}
+
+fn default_efficiency() -> f32 {
+    1.0
+}
+
+impl InteractionRule {
+    /// A rule without a cooldown
+    pub fn new(actor: EntityType, target: EntityType, kind: InteractionKind, amount: u32, efficiency: f32) -> Self {
+        InteractionRule { actor, target, kind, amount, efficiency, cooldown: 0.0 }
+    }
+
+    /// The built-in food web: gatherers eat resources, predators hunt gatherers
+    /// and scavengers eat carcasses
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(EntityType::Gatherer, EntityType::Resource, InteractionKind::Consume, 30, 1.0),
+            Self::new(EntityType::Predator, EntityType::Gatherer, InteractionKind::Hunt, 40, 0.5),
+            Self::new(EntityType::Scavenger, EntityType::Carcass, InteractionKind::Consume, 20, 0.8),
+        ]
+    }
+
+    /// Carry the rule out between two touching entities, returning the energy
+    /// the actor gained if anything happened
+    pub fn apply(&self, actor: &mut Entity, target: &mut Entity) -> Option<u32> {
+        let gained = match self.kind {
+            InteractionKind::Consume | InteractionKind::Hunt => {
+                if target.energy == 0 {
+                    return None;
+                }
+                let taken = target.energy.min(self.amount);
+                target.energy -= taken;
+                let gained = if self.kind == InteractionKind::Consume {
+                    actor.take_food((taken as f32 * self.efficiency) as u32)
+                } else {
+                    let gained = ((taken as f32 * self.efficiency) as u32).min(actor.max_energy - actor.energy);
+                    actor.energy += gained;
+                    gained
+                };
+
+                if self.kind == InteractionKind::Hunt {
+                    actor.time_since_last_hunt = 0.0;
+                }
+                gained
+            }
+            InteractionKind::Compete => {
+                actor.energy = actor.energy.saturating_sub(self.amount);
+                target.energy = target.energy.saturating_sub(self.amount);
+                0
+            }
+            InteractionKind::Mutualism => {
+                let share = (self.amount as f32 * self.efficiency) as u32;
+                let gained = share.min(actor.max_energy - actor.energy);
+                actor.energy += gained;
+                target.energy = (target.energy + share).min(target.max_energy);
+                gained
+            }
+            InteractionKind::None | InteractionKind::Other(_) => return None,
+        };
+        Some(gained)
+    }
+}
+
+/// Interaction rules looked up by actor and target type
+#[derive(Debug, Clone, Default)]
+pub struct InteractionTable {
+    rules: BTreeMap<(&'static str, &'static str), InteractionRule>,
+}
+
+impl InteractionTable {
+    /// Build a table from rules; later rules for the same pair replace earlier ones
+    pub fn new(rules: impl IntoIterator<Item = InteractionRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|rule| ((rule.actor.name(), rule.target.name()), rule))
+            .collect();
+        InteractionTable { rules }
+    }
+
+    /// What an actor type does to a target type, if anything
+    pub fn get(&self, actor: EntityType, target: EntityType) -> Option<&InteractionRule> {
+        self.rules
+            .get(&(actor.name(), target.name()))
+            .filter(|rule| rule.kind != InteractionKind::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    fn gatherer(id: u64, energy: u32) -> Entity {
+        let mut entity = Entity::new_gatherer(id, Position::new(0.0, 0.0));
+        entity.energy = energy;
+        entity
+    }
+
+    #[test]
+    fn hunting_takes_what_the_target_has_and_keeps_a_share() {
+        let rule = InteractionRule::new(EntityType::Predator, EntityType::Gatherer, InteractionKind::Hunt, 40, 0.5);
+        let mut predator = Entity::new_predator(1, Position::new(0.0, 0.0));
+        predator.energy = 100;
+        predator.time_since_last_hunt = 30.0;
+        let mut prey = gatherer(2, 30);
+
+        assert_eq!(rule.apply(&mut predator, &mut prey), Some(15));
+        assert_eq!(prey.energy, 0);
+        assert_eq!(predator.energy, 115);
+        assert_eq!(predator.time_since_last_hunt, 0.0);
+        // Nothing is left to take
+        assert_eq!(rule.apply(&mut predator, &mut prey), None);
+    }
+
+    #[test]
+    fn consuming_stops_at_a_full_stomach() {
+        let rule = InteractionRule::new(EntityType::Gatherer, EntityType::Resource, InteractionKind::Consume, 30, 1.0);
+        let mut eater = gatherer(1, 190);
+        let mut food = Entity::new_resource(2, Position::new(0.0, 0.0));
+        food.energy = 80;
+
+        assert_eq!(rule.apply(&mut eater, &mut food), Some(10));
+        assert_eq!(eater.energy, 200);
+        assert_eq!(food.energy, 50);
+    }
+
+    #[test]
+    fn competing_and_mutualism_affect_both_sides() {
+        let compete = InteractionRule::new(EntityType::Gatherer, EntityType::Gatherer, InteractionKind::Compete, 20, 1.0);
+        let (mut a, mut b) = (gatherer(1, 100), gatherer(2, 10));
+        assert_eq!(compete.apply(&mut a, &mut b), Some(0));
+        assert_eq!((a.energy, b.energy), (80, 0));
+
+        let mutualism = InteractionRule::new(EntityType::Gatherer, EntityType::Gatherer, InteractionKind::Mutualism, 20, 0.5);
+        let (mut a, mut b) = (gatherer(1, 100), gatherer(2, 195));
+        assert_eq!(mutualism.apply(&mut a, &mut b), Some(10));
+        assert_eq!((a.energy, b.energy), (110, 200));
+    }
+
+    #[test]
+    fn later_rules_replace_earlier_ones_and_none_switches_a_pair_off() {
+        let table = InteractionTable::new(InteractionRule::defaults().into_iter().chain([
+            InteractionRule::new(EntityType::Gatherer, EntityType::Resource, InteractionKind::Consume, 5, 1.0),
+            InteractionRule::new(EntityType::Predator, EntityType::Gatherer, InteractionKind::None, 0, 1.0),
+        ]));
+
+        assert_eq!(table.get(EntityType::Gatherer, EntityType::Resource).map(|rule| rule.amount), Some(5));
+        assert_eq!(table.get(EntityType::Predator, EntityType::Gatherer), None);
+        assert_eq!(table.get(EntityType::Resource, EntityType::Gatherer), None);
+
+        let mut a = gatherer(1, 100);
+        let mut b = gatherer(2, 100);
+        let none = InteractionRule::new(EntityType::Gatherer, EntityType::Gatherer, InteractionKind::None, 50, 1.0);
+        assert_eq!(none.apply(&mut a, &mut b), None);
+        assert_eq!((a.energy, b.energy), (100, 100));
+    }
+}
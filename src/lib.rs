@@ -0,0 +1,28 @@
+pub mod analysis;
+pub mod behavior_tree;
+pub mod brain;
+pub mod config;
+pub mod disease;
+pub mod energy;
+pub mod entity;
+pub mod food_web;
+pub mod grid;
+pub mod gym;
+pub mod interactions;
+pub mod life;
+pub mod nest;
+pub mod neural;
+pub mod obstacle;
+pub mod pack;
+pub mod pheromone;
+pub mod position;
+pub mod renderer;
+pub mod replay;
+pub mod scripting;
+pub mod simulation;
+pub mod species;
+pub mod stats;
+pub mod steering;
+pub mod territory;
+pub mod timeline;
+pub mod utility;
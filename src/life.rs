@@ -0,0 +1,44 @@
+use crate::config::{LifeConfig, StageConfig};
+
+/// Phase of an entity's life, set by how far through its lifespan it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LifeStage {
+    Juvenile,
+    #[default]
+    Adult,
+    Elder,
+}
+
+impl LifeStage {
+    pub const ALL: [LifeStage; 3] = [LifeStage::Juvenile, LifeStage::Adult, LifeStage::Elder];
+
+    /// Name of the stage as used in statistics and scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            LifeStage::Juvenile => "juvenile",
+            LifeStage::Adult => "adult",
+            LifeStage::Elder => "elder",
+        }
+    }
+
+    /// Stage reached at an age, given the entity's lifespan
+    pub fn at(age: f32, lifespan: f32, settings: &LifeConfig) -> Self {
+        let progress = age / lifespan.max(f32::EPSILON);
+        if progress < settings.juvenile_until {
+            LifeStage::Juvenile
+        } else if progress < settings.elder_from {
+            LifeStage::Adult
+        } else {
+            LifeStage::Elder
+        }
+    }
+
+    /// Speed, metabolism and fertility settings for the stage
+    pub fn settings(self, settings: &LifeConfig) -> &StageConfig {
+        match self {
+            LifeStage::Juvenile => &settings.juvenile,
+            LifeStage::Adult => &settings.adult,
+            LifeStage::Elder => &settings.elder,
+        }
+    }
+}
@@ -1,28 +1,59 @@
-use minifb::{Key, Window, WindowOptions};
+use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
-mod entity;
-mod position;
-mod renderer;
-mod simulation;
+mod cli;
 
-use simulation::Simulation;
-use renderer::Renderer;
+use cli::CliOptions;
+use rustcolony::analysis::{self, TimeSeries};
+use rustcolony::config::SimulationConfig;
+use rustcolony::replay::{Intervention, Replay};
+use rustcolony::simulation::Simulation;
+use rustcolony::renderer::Renderer;
+use rustcolony::scripting::ScriptHost;
+use rustcolony::timeline::Timeline;
 
 const WINDOW_WIDTH: usize = 800;
 const WINDOW_HEIGHT: usize = 600;
+/// Seconds per update in recorded and replayed runs, which need a fixed step to repeat exactly
+const TIME_STEP: f32 = 1.0 / 60.0;
+/// How far the arrow keys jump through a replay or the rewind timeline
+const SEEK_SECONDS: f32 = 10.0;
+
+/// Whether a run is live, being recorded, or a recording being played back
+pub enum Session {
+    Live,
+    Recording { replay: Replay, path: PathBuf },
+    Playback { replay: Replay },
+}
 
 /// Main application struct that manages the core systems
 pub struct Application {
     window: Window,
     simulation: Simulation,
     renderer: Renderer,
+    scripts: Option<ScriptHost>,
+    session: Session,
+    /// Keyframes to rewind to, unless turned off in the config
+    timeline: Option<Timeline>,
+    paused: bool,
+    /// Whether the run was taken back to a keyframe and has not run on since
+    rewound: bool,
     last_update: Instant,
+    save_brains_path: Option<PathBuf>,
+    stats_path: Option<PathBuf>,
+    energy_report: bool,
 }
 
 impl Application {
     /// Create a new application instance
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(
+        mut simulation: Simulation,
+        mut session: Session,
+        save_brains_path: Option<PathBuf>,
+        stats_path: Option<PathBuf>,
+        energy_report: bool,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut window = Window::new(
             "Rust Colony Simulation",
             WINDOW_WIDTH,
@@ -32,14 +63,37 @@ impl Application {
         
         window.limit_update_rate(Some(Duration::from_micros(16600)));
 
-        let simulation = Simulation::new(WINDOW_WIDTH, WINDOW_HEIGHT);
         let renderer = Renderer::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+        let scripts = match &mut session {
+            Session::Live => load_scripts(&mut simulation)?,
+            Session::Recording { replay, .. } => {
+                let mut scripts = load_scripts(&mut simulation)?;
+                if let Some(scripts) = &mut scripts {
+                    replay.keep_script(scripts);
+                }
+                scripts
+            }
+            Session::Playback { replay } => replay.scripts(&mut simulation)?,
+        };
+        let settings = &simulation.config().timeline;
+        let mut timeline = settings.enabled.then(|| Timeline::new(settings.keyframe_interval, settings.keyframes));
+        if let Some(timeline) = &mut timeline {
+            timeline.capture(&simulation, scripts.as_ref());
+        }
 
         Ok(Application {
             window,
             simulation,
             renderer,
+            scripts,
+            session,
+            timeline,
+            paused: false,
+            rewound: false,
             last_update: Instant::now(),
+            save_brains_path,
+            stats_path,
+            energy_report,
         })
     }
 
@@ -51,13 +105,16 @@ impl Application {
         while self.window.is_open() && !self.window.is_key_down(Key::Escape) {
             let now = Instant::now();
             let delta_time = now.duration_since(self.last_update).as_secs_f32();
-            
-            // Update simulation (mutable borrow)
-            self.simulation.update(delta_time);
+            if !self.paused {
+                self.step(delta_time);
+            }
             
             // Render the world (immutable borrow of entities)
             self.renderer.clear();
-            self.renderer.draw_world(self.simulation.get_entities());
+            self.renderer.draw_world(&self.simulation);
+            if let Some(span) = self.timeline_span() {
+                self.renderer.draw_timeline(span, self.simulation.time());
+            }
             
             // Update window with new frame
             self.window
@@ -70,33 +127,310 @@ impl Application {
         }
 
         println!("Simulation ended");
+        self.save_brains();
+        self.save_stats();
+        self.save_replay();
+        if self.energy_report {
+            print!("{}", self.simulation.energy_ledger().report());
+        }
         Ok(())
     }
 
+    /// Advance the simulation by one update (mutable borrow), with script
+    /// hooks around it, and keep a keyframe when one is due
+    fn step(&mut self, delta_time: f32) {
+        self.branch();
+        match &mut self.session {
+            Session::Live => {
+                if let Some(scripts) = &mut self.scripts {
+                    scripts.before_update(&mut self.simulation);
+                }
+                self.simulation.update(delta_time);
+                if let Some(scripts) = &mut self.scripts {
+                    scripts.after_update(&mut self.simulation, delta_time);
+                }
+            }
+            Session::Recording { replay, .. } => replay.step(&mut self.simulation, self.scripts.as_mut()),
+            Session::Playback { replay } => {
+                replay.step(&mut self.simulation, self.scripts.as_mut());
+                if self.simulation.ticks() == replay.ticks {
+                    self.paused = true;
+                    println!("Replay finished at {:.1}s, press Space to run on", self.simulation.time());
+                }
+            }
+        }
+        if let Some(timeline) = &mut self.timeline {
+            timeline.capture(&self.simulation, self.scripts.as_ref());
+        }
+    }
+
+    /// Make a change to the world, noting it in the recording if there is one.
+    /// Recorded changes are made at the start of the next update, as on playback
+    fn intervene(&mut self, intervention: Intervention) {
+        if matches!(self.session, Session::Playback { .. }) {
+            return;
+        }
+        self.branch();
+        match &mut self.session {
+            Session::Live => intervention.apply(&mut self.simulation),
+            Session::Recording { replay, .. } => replay.push(&self.simulation, intervention),
+            Session::Playback { .. } => {}
+        }
+        println!("{}", intervention.describe());
+    }
+
+    /// Once a rewound run is changed or runs on, drop the future it left
+    /// behind: later keyframes and, when recording, later interventions
+    fn branch(&mut self) {
+        if !self.rewound {
+            return;
+        }
+        self.rewound = false;
+        let tick = self.simulation.ticks();
+        if let Some(timeline) = &mut self.timeline {
+            timeline.truncate_after(tick);
+        }
+        if let Session::Recording { replay, .. } = &mut self.session {
+            replay.truncate(tick);
+        }
+        println!("Branched a new future from {:.1}s", self.simulation.time());
+    }
+
+    /// Jump to `seconds` of simulated time. A replay goes exactly there, from
+    /// the nearest keyframe or else by re-running it from the start; a live
+    /// run goes back to the latest keyframe at or before that time and pauses
+    fn seek(&mut self, seconds: f32) -> Result<(), Box<dyn std::error::Error>> {
+        let current = self.simulation.ticks();
+        if let Session::Playback { replay } = &self.session {
+            let target = replay.tick_at(seconds).min(replay.ticks);
+            let keyframe = self
+                .timeline
+                .as_ref()
+                .and_then(|timeline| timeline.before_tick(target))
+                .filter(|keyframe| target < current || keyframe.simulation.ticks() > current)
+                .cloned();
+            match keyframe {
+                Some(keyframe) => keyframe.restore(&mut self.simulation, self.scripts.as_mut()),
+                None if target < current => {
+                    self.simulation = replay.start();
+                    self.scripts = replay.scripts(&mut self.simulation)?;
+                    if let Some(timeline) = &mut self.timeline {
+                        timeline.truncate_after(target);
+                    }
+                }
+                None => {}
+            }
+            while self.simulation.ticks() < target {
+                replay.step(&mut self.simulation, self.scripts.as_mut());
+                if let Some(timeline) = &mut self.timeline {
+                    timeline.capture(&self.simulation, self.scripts.as_ref());
+                }
+            }
+            println!("Replay at {:.1}s of {:.1}s", self.simulation.time(), replay.duration());
+            return Ok(());
+        }
+
+        let time = self.simulation.time();
+        let keyframe = self
+            .timeline
+            .as_ref()
+            .and_then(|timeline| timeline.before_time(seconds))
+            .filter(|keyframe| {
+                let tick = keyframe.simulation.ticks();
+                tick != current && (seconds < time || tick > current)
+            })
+            .cloned();
+        if let Some(keyframe) = keyframe {
+            keyframe.restore(&mut self.simulation, self.scripts.as_mut());
+            self.rewound = true;
+            self.paused = true;
+            println!("Rewound to {:.1}s, press Enter to run on from here", self.simulation.time());
+        }
+        Ok(())
+    }
+
+    /// Stretch of simulated time the timeline covers: all of a replay, or
+    /// from the earliest keyframe to now
+    fn timeline_span(&self) -> Option<(f32, f32)> {
+        match &self.session {
+            Session::Playback { replay } => Some((0.0, replay.duration())),
+            _ => {
+                let (start, end) = self.timeline.as_ref()?.span()?;
+                Some((start, end.max(self.simulation.time())))
+            }
+        }
+    }
+
     /// Handle user input
     fn handle_input(&mut self) {
-        // Add random resources on space key
-        if self.window.is_key_pressed(Key::Space, minifb::KeyRepeat::No) {
-            self.simulation.add_random_resources(5);
-            println!("Added 5 new resources");
+        let playback = matches!(self.session, Session::Playback { .. });
+
+        // Pause on Enter key, or on space key in a replay
+        if self.window.is_key_pressed(Key::Enter, minifb::KeyRepeat::No)
+            || (playback && self.window.is_key_pressed(Key::Space, minifb::KeyRepeat::No))
+        {
+            self.paused = !self.paused;
         }
-        
-        // Add gatherers on G key
-        if self.window.is_key_pressed(Key::G, minifb::KeyRepeat::No) {
-            self.simulation.add_random_gatherers(3);
-            println!("Added 3 new gatherers");
+
+        // Seek with the arrow and Home keys, or by dragging along the timeline
+        let time = self.simulation.time();
+        let mut seek_to = if self.window.is_key_pressed(Key::Right, minifb::KeyRepeat::Yes) {
+            Some(time + SEEK_SECONDS)
+        } else if self.window.is_key_pressed(Key::Left, minifb::KeyRepeat::Yes) {
+            Some(time - SEEK_SECONDS)
+        } else if self.window.is_key_pressed(Key::Home, minifb::KeyRepeat::No) {
+            Some(0.0)
+        } else {
+            None
+        };
+        if self.window.get_mouse_down(MouseButton::Left) {
+            let mouse = self.window.get_mouse_pos(MouseMode::Discard);
+            if let (Some((x, y)), Some(span)) = (mouse, self.timeline_span()) {
+                if let Some(seconds) = self.renderer.timeline_time_at(x, y, span) {
+                    seek_to = Some(seconds);
+                    self.paused = true;
+                }
+            }
         }
-        
-        // Add predators on P key
-        if self.window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
-            self.simulation.add_random_predators(1);
-            println!("Added 1 new predator");
+        if let Some(seconds) = seek_to {
+            if let Err(e) = self.seek(seconds) {
+                eprintln!("Could not seek: {}", e);
+            }
+        }
+
+        if !playback {
+            // Add random resources on space key
+            if self.window.is_key_pressed(Key::Space, minifb::KeyRepeat::No) {
+                self.intervene(Intervention::AddResources { count: 5 });
+            }
+
+            // Add gatherers on G key
+            if self.window.is_key_pressed(Key::G, minifb::KeyRepeat::No) {
+                self.intervene(Intervention::AddGatherers { count: 3 });
+            }
+
+            // Add predators on P key
+            if self.window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+                self.intervene(Intervention::AddPredators { count: 1 });
+            }
+        }
+
+        // Toggle the territory overlay on T key
+        if self.window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+            self.renderer.show_territory = !self.renderer.show_territory;
+        }
+
+        // Save the best neural brains on B key
+        if self.window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
+            self.save_brains();
+        }
+    }
+
+    /// Write the recorded time series, if a stats path was given
+    fn save_stats(&self) {
+        let Some(path) = &self.stats_path else { return };
+        let stats = self.simulation.stats();
+        match stats.save(path) {
+            Ok(()) => println!("Saved {} stats samples to {}", stats.len(), path.display()),
+            Err(e) => eprintln!("Could not save stats to {}: {}", path.display(), e),
+        }
+    }
+
+    /// Write the recording, if the run was recorded
+    fn save_replay(&mut self) {
+        let Session::Recording { replay, path } = &mut self.session else { return };
+        replay.ticks = self.simulation.ticks();
+        match replay.save(&*path) {
+            Ok(()) => println!(
+                "Saved a replay of {:.1}s with {} interventions to {}",
+                replay.duration(),
+                replay.events.len(),
+                path.display()
+            ),
+            Err(e) => eprintln!("Could not save the replay to {}: {}", path.display(), e),
+        }
+    }
+
+    /// Write the best neural brains so far, if a save path was given
+    fn save_brains(&self) {
+        let Some(path) = &self.save_brains_path else { return };
+        let brains = self.simulation.best_brains();
+        if brains.champions.is_empty() {
+            return;
+        }
+
+        match brains.save(path) {
+            Ok(()) => println!("Saved {} brains to {}", brains.champions.len(), path.display()),
+            Err(e) => eprintln!("Could not save brains to {}: {}", path.display(), e),
         }
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = match CliOptions::from_env() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(analyze) = &options.analyze {
+        let series = TimeSeries::load(&analyze.stats_path)?;
+        let analysis = analysis::analyze(&series, &analyze.prey, &analyze.predator, analyze.smoothing)?;
+        print!("{}", analysis.report());
+        return Ok(());
+    }
+
+    let (simulation, session) = match &options.replay_path {
+        Some(path) => {
+            let replay = Replay::load(path)?;
+            println!("Replaying {} ({:.1}s, {} interventions)", path.display(), replay.duration(), replay.events.len());
+            println!("Space pauses, Left and Right seek {}s, Home restarts", SEEK_SECONDS);
+            (replay.start(), Session::Playback { replay })
+        }
+        None => {
+            let mut config = match &options.config_path {
+                Some(path) => {
+                    println!("Loading config from {}", path.display());
+                    SimulationConfig::load(path)?
+                }
+                None => SimulationConfig::default(),
+            };
+            if let Some(preset) = options.preset {
+                config.preset = preset;
+            }
+            if options.energy_report {
+                config.energy.audit = true;
+            }
+
+            let simulation = Simulation::with_config(WINDOW_WIDTH, WINDOW_HEIGHT, config);
+            let session = match options.record_path {
+                Some(path) => Session::Recording { replay: Replay::record(&simulation, TIME_STEP), path },
+                None => Session::Live,
+            };
+            (simulation, session)
+        }
+    };
+
     // Create and run the application
-    let mut app = Application::new()?;
+    let mut app = Application::new(
+        simulation,
+        session,
+        options.save_brains_path,
+        options.stats_path,
+        options.energy_report,
+    )?;
+    if let Some(seconds) = options.seek {
+        app.seek(seconds)?;
+    }
     app.run()
-} 
\ No newline at end of file
+} 
+
+/// Load the script configured for a simulation, if any
+fn load_scripts(simulation: &mut Simulation) -> Result<Option<ScriptHost>, Box<dyn std::error::Error>> {
+    match simulation.config().scripting.file.clone() {
+        Some(path) => Ok(Some(ScriptHost::load(path, simulation)?)),
+        None => Ok(None),
+    }
+}
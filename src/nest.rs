@@ -0,0 +1,30 @@
+use crate::config::FactionId;
+use crate::position::Position;
+
+/// Unique identifier for nests
+pub type NestId = u32;
+
+/// A home gatherers haul food back to, which feeds its members and raises new ones
+#[derive(Debug, Clone)]
+pub struct Nest {
+    pub id: NestId,
+    pub position: Position,
+    pub radius: f32,
+    pub faction: Option<FactionId>,
+    /// Food energy in store
+    pub food: u32,
+    /// Gatherers raised from stored food so far
+    pub raised: u32,
+}
+
+impl Nest {
+    /// Create an empty nest
+    pub fn new(id: NestId, position: Position, radius: f32, faction: Option<FactionId>) -> Self {
+        Nest { id, position, radius, faction, food: 0, raised: 0 }
+    }
+
+    /// Whether a position is inside the nest
+    pub fn contains(&self, position: &Position) -> bool {
+        self.position.distance_squared_to(position) <= self.radius * self.radius
+    }
+}
@@ -0,0 +1,260 @@
+use crate::brain::{self, SteeringPlan, WorldView, SENSOR_COUNT};
+use crate::entity::{Entity, EntityType};
+use crate::position::Vec2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The network reads the standard sensor vector
+pub const INPUT_COUNT: usize = SENSOR_COUNT;
+
+/// Outputs: steering direction (x, y), throttle and the urge to reproduce
+pub const OUTPUT_COUNT: usize = 4;
+
+/// Fitness credited per child raised, on top of seconds survived
+const OFFSPRING_FITNESS: f32 = 30.0;
+
+/// A fixed-topology feed-forward network with one tanh hidden layer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NeuralNetwork {
+    pub hidden: usize,
+    /// Input-to-hidden weights, one row of `INPUT_COUNT + 1` (with bias) per hidden neuron
+    pub hidden_weights: Vec<f32>,
+    /// Hidden-to-output weights, one row of `hidden + 1` (with bias) per output
+    pub output_weights: Vec<f32>,
+}
+
+impl NeuralNetwork {
+    /// Create a network with small random weights
+    pub fn random(hidden: usize, rng: &mut impl Rng) -> Self {
+        let mut weight = || rng.gen_range(-1.0..1.0);
+        NeuralNetwork {
+            hidden,
+            hidden_weights: (0..hidden * (INPUT_COUNT + 1)).map(|_| weight()).collect(),
+            output_weights: (0..OUTPUT_COUNT * (hidden + 1)).map(|_| weight()).collect(),
+        }
+    }
+
+    /// Check the weights have the lengths the hidden-layer size calls for
+    pub fn check_shape(&self) -> Result<(), String> {
+        let expected = (self.hidden * (INPUT_COUNT + 1), OUTPUT_COUNT * (self.hidden + 1));
+        let actual = (self.hidden_weights.len(), self.output_weights.len());
+        if actual != expected {
+            return Err(format!(
+                "a network with {} hidden neurons needs {} hidden and {} output weights, not {} and {}",
+                self.hidden, expected.0, expected.1, actual.0, actual.1
+            ));
+        }
+        Ok(())
+    }
+
+    /// Run the network forward
+    pub fn feed_forward(&self, inputs: &[f32; INPUT_COUNT]) -> [f32; OUTPUT_COUNT] {
+        let hidden: Vec<f32> = self
+            .hidden_weights
+            .chunks(INPUT_COUNT + 1)
+            .map(|row| {
+                let sum: f32 = row.iter().zip(inputs).map(|(w, x)| w * x).sum::<f32>() + row[INPUT_COUNT];
+                sum.tanh()
+            })
+            .collect();
+
+        let mut outputs = [0.0; OUTPUT_COUNT];
+        for (output, row) in outputs.iter_mut().zip(self.output_weights.chunks(self.hidden + 1)) {
+            let sum: f32 = row.iter().zip(&hidden).map(|(w, h)| w * h).sum::<f32>() + row[self.hidden];
+            *output = sum.tanh();
+        }
+        outputs
+    }
+
+    /// Perturb each weight with probability `rate` by Gaussian noise of the
+    /// given strength; a NaN rate mutates nothing
+    pub fn mutate(&mut self, rate: f32, strength: f32, rng: &mut impl Rng) {
+        let rate = if rate.is_nan() { 0.0 } else { rate.clamp(0.0, 1.0) };
+        for weight in self.hidden_weights.iter_mut().chain(self.output_weights.iter_mut()) {
+            if rng.gen_bool(rate as f64) {
+                *weight += gaussian(rng) * strength;
+            }
+        }
+    }
+
+    /// Sense the world and turn the network's outputs into steering
+    pub fn decide(&self, entity: &Entity, world: &WorldView) -> SteeringPlan {
+        let outputs = self.feed_forward(&brain::sense(entity, world));
+
+        let direction = Vec2::new(outputs[0], outputs[1]).normalized();
+        let throttle = (outputs[2] + 1.0) / 2.0;
+        let desired = direction * entity.max_speed * throttle;
+
+        SteeringPlan {
+            reproduce: outputs[3] > 0.5,
+            ..SteeringPlan::steer(desired - entity.velocity, entity.max_speed)
+        }
+    }
+}
+
+/// Standard normal sample via the Box-Muller transform
+fn gaussian(rng: &mut impl Rng) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen_range(0.0..1.0);
+    (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+}
+
+/// A network that did well, kept so later worlds can start from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Champion {
+    pub entity_type: EntityType,
+    pub fitness: f32,
+    pub network: NeuralNetwork,
+}
+
+/// The best networks seen so far for each entity type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HallOfFame {
+    pub champions: Vec<Champion>,
+}
+
+impl HallOfFame {
+    /// Champions kept per entity type
+    pub const CAPACITY: usize = 10;
+
+    /// Fitness of an entity: seconds survived plus credit for each child
+    pub fn fitness(entity: &Entity) -> f32 {
+        entity.age + entity.offspring as f32 * OFFSPRING_FITNESS
+    }
+
+    /// Record an entity's network if it ranks among the best of its type
+    pub fn consider(&mut self, entity: &Entity) {
+        let Some(network) = &entity.network else { return };
+
+        self.champions.push(Champion {
+            entity_type: entity.entity_type,
+            fitness: Self::fitness(entity),
+            network: network.clone(),
+        });
+        self.champions.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+        let mut kept = 0;
+        self.champions.retain(|c| {
+            if c.entity_type != entity.entity_type {
+                return true;
+            }
+            kept += 1;
+            kept <= Self::CAPACITY
+        });
+    }
+
+    /// Champions of one entity type, best first
+    pub fn of_type(&self, entity_type: EntityType) -> impl Iterator<Item = &Champion> {
+        self.champions.iter().filter(move |c| c.entity_type == entity_type)
+    }
+
+    /// Load champions saved by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read brains {}: {}", path.display(), e))?;
+        let hall: HallOfFame =
+            serde_json::from_str(&text).map_err(|e| format!("Invalid brains file {}: {}", path.display(), e))?;
+        for (index, champion) in hall.champions.iter().enumerate() {
+            champion
+                .network
+                .check_shape()
+                .map_err(|e| format!("Invalid brains file {}: champion {}: {}", path.display(), index + 1, e))?;
+        }
+        Ok(hall)
+    }
+
+    /// Write the champions as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn scratch_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("rustcolony-{}-{}", std::process::id(), name))
+    }
+
+    fn with_network(entity_type: EntityType, age: f32, network: &NeuralNetwork) -> Entity {
+        let mut entity = Entity::new_of_type(entity_type, 1, Position::new(0.0, 0.0));
+        entity.age = age;
+        entity.network = Some(network.clone());
+        entity
+    }
+
+    #[test]
+    fn feed_forward_uses_biases_and_stays_in_range() {
+        let mut network = NeuralNetwork::random(3, &mut StdRng::seed_from_u64(1));
+        assert!(network.check_shape().is_ok());
+        let outputs = network.feed_forward(&[1.0; INPUT_COUNT]);
+        assert!(outputs.iter().all(|o| o.abs() < 1.0));
+
+        network.hidden_weights.iter_mut().for_each(|w| *w = 0.0);
+        network.output_weights.iter_mut().for_each(|w| *w = 0.0);
+        let bias = network.output_weights.len() / OUTPUT_COUNT - 1;
+        network.output_weights[bias] = 0.5;
+        let outputs = network.feed_forward(&[1.0; INPUT_COUNT]);
+        assert_eq!(outputs, [0.5f32.tanh(), 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn mutation_follows_the_rate() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let original = NeuralNetwork::random(4, &mut rng);
+
+        let mut network = original.clone();
+        network.mutate(0.0, 1.0, &mut rng);
+        assert_eq!(network, original);
+        network.mutate(f32::NAN, 1.0, &mut rng);
+        assert_eq!(network, original);
+
+        network.mutate(1.0, 0.5, &mut rng);
+        let changed = network.hidden_weights.iter().zip(&original.hidden_weights).filter(|(a, b)| a != b).count();
+        assert_eq!(changed, original.hidden_weights.len());
+    }
+
+    #[test]
+    fn hall_of_fame_keeps_the_best_of_each_type() {
+        let network = NeuralNetwork::random(2, &mut StdRng::seed_from_u64(3));
+        let mut hall = HallOfFame::default();
+        hall.consider(&with_network(EntityType::Predator, 5.0, &network));
+        for age in 0..15 {
+            hall.consider(&with_network(EntityType::Gatherer, age as f32, &network));
+        }
+        hall.consider(&Entity::new_gatherer(2, Position::new(0.0, 0.0)));
+
+        let gatherers: Vec<f32> = hall.of_type(EntityType::Gatherer).map(|c| c.fitness).collect();
+        assert_eq!(gatherers, (5..15).rev().map(|age| age as f32).collect::<Vec<_>>());
+        assert_eq!(hall.of_type(EntityType::Predator).count(), 1);
+    }
+
+    #[test]
+    fn saved_brains_load_back_and_bad_shapes_are_rejected() {
+        let network = NeuralNetwork::random(2, &mut StdRng::seed_from_u64(4));
+        let mut hall = HallOfFame::default();
+        hall.consider(&with_network(EntityType::Gatherer, 10.0, &network));
+
+        let path = scratch_file("brains.json");
+        hall.save(&path).unwrap();
+        let loaded = HallOfFame::load(&path).unwrap();
+        assert_eq!(loaded.champions[0].network, network);
+
+        hall.champions[0].network.output_weights.pop();
+        hall.save(&path).unwrap();
+        let error = HallOfFame::load(&path).unwrap_err().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains("champion 1"), "{}", error);
+    }
+}
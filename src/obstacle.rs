@@ -0,0 +1,63 @@
+use crate::position::Position;
+
+/// A circular rock that blocks movement and line of sight
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obstacle {
+    pub position: Position,
+    pub radius: f32,
+}
+
+impl Obstacle {
+    /// Create a new obstacle
+    pub fn new(position: Position, radius: f32) -> Self {
+        Obstacle { position, radius }
+    }
+
+    /// Check if a point lies inside the obstacle
+    pub fn contains(&self, point: &Position) -> bool {
+        self.position.distance_to(point) < self.radius
+    }
+
+    /// Check if the straight segment between two points passes through the obstacle
+    pub fn blocks_segment(&self, start: &Position, end: &Position) -> bool {
+        let (sx, sy) = (start.x, start.y);
+        let (ex, ey) = (end.x, end.y);
+        let (cx, cy) = (self.position.x, self.position.y);
+
+        let dx = ex - sx;
+        let dy = ey - sy;
+        let length_squared = dx * dx + dy * dy;
+
+        // Closest point on the segment to the obstacle centre
+        let t = if length_squared > 0.0 {
+            (((cx - sx) * dx + (cy - sy) * dy) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let px = sx + t * dx - cx;
+        let py = sy + t * dy - cy;
+
+        px * px + py * py < self.radius * self.radius
+    }
+
+    /// Push a position that ended up inside the obstacle back onto its edge
+    pub fn push_out(&self, point: &mut Position) {
+        let distance = self.position.distance_to(point);
+        if distance >= self.radius {
+            return;
+        }
+
+        if distance > 0.0 {
+            let ratio = self.radius / distance;
+            point.x = self.position.x + (point.x - self.position.x) * ratio;
+            point.y = self.position.y + (point.y - self.position.y) * ratio;
+        } else {
+            point.x = self.position.x + self.radius;
+        }
+    }
+}
+
+/// Check if any obstacle blocks the view between two points
+pub fn has_line_of_sight(obstacles: &[Obstacle], from: &Position, to: &Position) -> bool {
+    !obstacles.iter().any(|obstacle| obstacle.blocks_segment(from, to))
+}
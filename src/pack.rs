@@ -0,0 +1,74 @@
+use crate::entity::{Entity, EntityId};
+
+/// Unique identifier for packs
+pub type PackId = u64;
+
+/// A group of predators that share a target and the energy from its kill
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub id: PackId,
+    pub leader: EntityId,
+    pub members: Vec<EntityId>,
+    pub target: Option<EntityId>,
+}
+
+impl Pack {
+    /// Create a new pack led by `leader`
+    pub fn new(id: PackId, leader: EntityId, members: Vec<EntityId>) -> Self {
+        Pack {
+            id,
+            leader,
+            members,
+            target: None,
+        }
+    }
+
+    /// Check if a predator belongs to the pack
+    pub fn contains(&self, entity_id: EntityId) -> bool {
+        self.members.contains(&entity_id)
+    }
+
+    /// Drop members that no longer exist and hand leadership to the
+    /// strongest survivor if the leader is gone
+    pub fn prune(&mut self, entities: &[Entity]) {
+        self.members.retain(|id| entities.iter().any(|e| e.id == *id && !e.is_dead()));
+
+        if !self.members.contains(&self.leader) {
+            if let Some(strongest) = self.strongest_member(entities) {
+                self.leader = strongest;
+            }
+        }
+    }
+
+    /// Member with the most energy
+    pub fn strongest_member(&self, entities: &[Entity]) -> Option<EntityId> {
+        entities
+            .iter()
+            .filter(|e| self.contains(e.id))
+            .max_by_key(|e| e.energy)
+            .map(|e| e.id)
+    }
+
+    /// Average time since members last fed
+    pub fn average_hunger_time(&self, entities: &[Entity]) -> f32 {
+        let times: Vec<f32> = entities
+            .iter()
+            .filter(|e| self.contains(e.id))
+            .map(|e| e.time_since_last_hunt)
+            .collect();
+
+        if times.is_empty() {
+            0.0
+        } else {
+            times.iter().sum::<f32>() / times.len() as f32
+        }
+    }
+
+    /// Slot of a member around the target, used to spread approach angles
+    pub fn slot_of(&self, entity_id: EntityId) -> Option<usize> {
+        self.members
+            .iter()
+            .filter(|id| **id != self.leader)
+            .position(|id| *id == entity_id)
+    }
+}
@@ -0,0 +1,107 @@
+use crate::config::PheromoneConfig;
+use crate::entity::Entity;
+use crate::grid::ScalarGrid;
+
+/// Scent trails laid by nest-dwelling gatherers.
+///
+/// Gatherers carrying food home lay a "food" trail and gatherers out
+/// searching lay a "home" trail. Each gatherer's scent is strongest just after
+/// it picks up food or leaves the nest and fades as it walks, so trails are
+/// strongest at their source and following one uphill leads there.
+#[derive(Debug, Clone)]
+pub struct Pheromones {
+    pub food: ScalarGrid,
+    pub home: ScalarGrid,
+}
+
+impl Pheromones {
+    /// Empty trail layers covering a world
+    pub fn new(world_width: usize, world_height: usize, cell_size: f32) -> Self {
+        Pheromones {
+            food: ScalarGrid::new(world_width, world_height, cell_size),
+            home: ScalarGrid::new(world_width, world_height, cell_size),
+        }
+    }
+
+    /// Let trails evaporate and spread for one time step
+    pub fn update(&mut self, settings: &PheromoneConfig, delta_time: f32) {
+        let kept = (1.0 - settings.evaporation * delta_time).clamp(0.0, 1.0);
+        let spread = settings.diffusion * delta_time;
+        for layer in [&mut self.food, &mut self.home] {
+            layer.scale(kept);
+            layer.diffuse(spread);
+        }
+    }
+
+    /// Mark the trail under a nest member and let its scent fade a little
+    pub fn lay(&mut self, entity: &mut Entity, settings: &PheromoneConfig, delta_time: f32) {
+        if entity.nest_id.is_none() || entity.scent <= 0.0 {
+            return;
+        }
+        let layer = if entity.carrying > 0 { &mut self.food } else { &mut self.home };
+        layer.add(&entity.position, settings.deposit * entity.scent * delta_time);
+        entity.scent = (entity.scent - settings.scent_fade * delta_time).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    fn settings(evaporation: f32, diffusion: f32) -> PheromoneConfig {
+        PheromoneConfig { enabled: true, evaporation, diffusion, ..PheromoneConfig::default() }
+    }
+
+    #[test]
+    fn trails_evaporate_in_proportion_to_their_strength() {
+        let mut pheromones = Pheromones::new(100, 100, 10.0);
+        let spot = Position::new(55.0, 55.0);
+        pheromones.food.add(&spot, 10.0);
+        pheromones.home.add(&spot, 4.0);
+
+        pheromones.update(&settings(0.1, 0.0), 1.0);
+        assert!((pheromones.food.get(&spot) - 9.0).abs() < 1e-5);
+        assert!((pheromones.home.get(&spot) - 3.6).abs() < 1e-5);
+
+        // A step long enough to evaporate everything can't go negative
+        pheromones.update(&settings(0.1, 0.0), 20.0);
+        assert_eq!(pheromones.food.total(), 0.0);
+    }
+
+    #[test]
+    fn diffusion_spreads_evenly_without_losing_scent() {
+        let mut pheromones = Pheromones::new(100, 100, 10.0);
+        let spot = Position::new(55.0, 55.0);
+        pheromones.food.add(&spot, 10.0);
+
+        pheromones.update(&settings(0.0, 0.4), 1.0);
+        assert!((pheromones.food.get(&spot) - 6.0).abs() < 1e-5);
+        for (dx, dy) in [(-10.0, 0.0), (10.0, 0.0), (0.0, -10.0), (0.0, 10.0)] {
+            let neighbour = Position::new(spot.x + dx, spot.y + dy);
+            assert!((pheromones.food.get(&neighbour) - 1.0).abs() < 1e-5);
+        }
+        assert!((pheromones.food.total() - 10.0).abs() < 1e-4);
+
+        // Uphill from a neighbour leads back to the source
+        assert!(pheromones.food.gradient(&Position::new(65.0, 55.0)).x < 0.0);
+    }
+
+    #[test]
+    fn only_scented_nest_members_lay_trails() {
+        let settings = settings(0.0, 0.0);
+        let mut pheromones = Pheromones::new(100, 100, 10.0);
+        let mut gatherer = Entity::new_gatherer(1, Position::new(55.0, 55.0));
+        gatherer.scent = 1.0;
+        pheromones.lay(&mut gatherer, &settings, 1.0);
+        assert_eq!(pheromones.home.total() + pheromones.food.total(), 0.0);
+
+        gatherer.nest_id = Some(0);
+        pheromones.lay(&mut gatherer, &settings, 1.0);
+        gatherer.carrying = 5;
+        pheromones.lay(&mut gatherer, &settings, 1.0);
+        assert!((pheromones.home.total() - settings.deposit).abs() < 1e-5);
+        assert!((pheromones.food.total() - settings.deposit * (1.0 - settings.scent_fade)).abs() < 1e-5);
+        assert!((gatherer.scent - (1.0 - 2.0 * settings.scent_fade)).abs() < 1e-5);
+    }
+}
@@ -1,51 +1,142 @@
+use std::ops::{Add, AddAssign, Mul, Sub};
+
 /// Represents a 2D position in the simulation world
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Position {
-    pub x: i32,
-    pub y: i32,
+    pub x: f32,
+    pub y: f32,
 }
 
 impl Position {
     /// Create a new position
-    pub fn new(x: i32, y: i32) -> Self {
+    pub fn new(x: f32, y: f32) -> Self {
         Position { x, y }
     }
 
     /// Calculate distance to another position
     pub fn distance_to(&self, other: &Position) -> f32 {
-        let dx = (self.x - other.x) as f32;
-        let dy = (self.y - other.y) as f32;
-        (dx * dx + dy * dy).sqrt()
+        self.distance_squared_to(other).sqrt()
     }
 
     /// Calculate squared distance (faster for comparisons)
-    pub fn distance_squared_to(&self, other: &Position) -> i32 {
+    pub fn distance_squared_to(&self, other: &Position) -> f32 {
         let dx = self.x - other.x;
         let dy = self.y - other.y;
         dx * dx + dy * dy
     }
 
-    /// Move towards another position by a given amount
-    pub fn move_towards(&mut self, target: &Position, distance: f32) {
-        let current_distance = self.distance_to(target);
-        if current_distance > 0.0 {
-            let ratio = distance / current_distance;
-            let dx = ((target.x - self.x) as f32 * ratio) as i32;
-            let dy = ((target.y - self.y) as f32 * ratio) as i32;
-            self.x += dx;
-            self.y += dy;
-        }
+    /// Vector pointing from this position to another
+    pub fn vector_to(&self, other: &Position) -> Vec2 {
+        Vec2::new(other.x - self.x, other.y - self.y)
+    }
+
+    /// Move by a displacement vector
+    pub fn translate(&mut self, offset: Vec2) {
+        self.x += offset.x;
+        self.y += offset.y;
+    }
+
+    /// Position displaced by a vector
+    pub fn offset(&self, offset: Vec2) -> Position {
+        Position::new(self.x + offset.x, self.y + offset.y)
     }
 
-    /// Add a random offset to the position
-    pub fn add_random_offset(&mut self, max_offset: i32, rng: &mut impl rand::Rng) {
-        self.x += rng.gen_range(-max_offset..=max_offset);
-        self.y += rng.gen_range(-max_offset..=max_offset);
+    /// Check if the position lies inside the world
+    pub fn is_within_bounds(&self, width: usize, height: usize) -> bool {
+        self.x >= 0.0 && self.x <= (width - 1) as f32 && self.y >= 0.0 && self.y <= (height - 1) as f32
     }
 
     /// Clamp position to stay within bounds
     pub fn clamp_to_bounds(&mut self, width: usize, height: usize) {
-        self.x = self.x.max(0).min(width as i32 - 1);
-        self.y = self.y.max(0).min(height as i32 - 1);
+        self.x = self.x.clamp(0.0, (width - 1) as f32);
+        self.y = self.y.clamp(0.0, (height - 1) as f32);
+    }
+
+    /// Pixel coordinates of the position, rounded to the nearest pixel
+    pub fn to_pixel(self) -> (i32, i32) {
+        (self.x.round() as i32, self.y.round() as i32)
+    }
+}
+
+/// A 2D vector used for velocities and steering forces
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    /// The zero vector
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    /// Create a new vector
+    pub fn new(x: f32, y: f32) -> Self {
+        Vec2 { x, y }
+    }
+
+    /// Unit vector pointing along a heading in radians
+    pub fn from_angle(angle: f32) -> Self {
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    /// Length of the vector
+    pub fn length(&self) -> f32 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    /// Heading of the vector in radians
+    pub fn angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// Unit vector in the same direction, or zero for the zero vector
+    pub fn normalized(&self) -> Vec2 {
+        let length = self.length();
+        if length > 0.0 {
+            Vec2::new(self.x / length, self.y / length)
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// Vector with the same direction and a length of at most `max`
+    pub fn truncated(&self, max: f32) -> Vec2 {
+        let length = self.length();
+        if length > max && length > 0.0 {
+            *self * (max / length)
+        } else {
+            *self
+        }
     }
-} 
\ No newline at end of file
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scale: f32) -> Vec2 {
+        Vec2::new(self.x * scale, self.y * scale)
+    }
+}
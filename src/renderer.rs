@@ -1,5 +1,18 @@
+use crate::disease::HealthState;
+use crate::config::FactionConfig;
 use crate::entity::{Entity, EntityType};
+use crate::grid::ScalarGrid;
+use crate::nest::Nest;
+use crate::obstacle::Obstacle;
+use crate::pheromone::Pheromones;
 use crate::position::Position;
+use crate::simulation::Simulation;
+use crate::species::SpeciesRegistry;
+use crate::territory::{Claimant, Territories};
+
+/// Distance of the timeline from the window edges, and its thickness, in pixels
+const TIMELINE_MARGIN: i32 = 10;
+const TIMELINE_THICKNESS: i32 = 4;
 
 /// Renderer manages the pixel buffer and handles drawing
 pub struct Renderer {
@@ -7,6 +20,8 @@ pub struct Renderer {
     width: usize,
     height: usize,
     background_color: u32,
+    /// Whether to draw the territory overlay
+    pub show_territory: bool,
 }
 
 impl Renderer {
@@ -17,6 +32,7 @@ impl Renderer {
             width,
             height,
             background_color: 0x000020,
+            show_territory: false,
         }
     }
 
@@ -31,14 +47,36 @@ impl Renderer {
     }
 
     /// Draw the entire world
-    pub fn draw_world(&mut self, entities: &[Entity]) {
+    pub fn draw_world(&mut self, simulation: &Simulation) {
+        let entities = simulation.get_entities();
         self.draw_background();
+        self.draw_soil(simulation.soil());
+        if simulation.config().pheromones.enabled {
+            self.draw_trails(simulation.pheromones());
+        }
+
+        for obstacle in simulation.get_obstacles() {
+            self.draw_obstacle(obstacle);
+        }
+
+        if self.show_territory && simulation.config().territory.enabled {
+            self.draw_territories(simulation.territories(), &simulation.config().factions);
+        }
+
+        let factions = &simulation.config().factions;
+        for nest in simulation.get_nests() {
+            let color = nest.faction.and_then(|id| factions.get(id)).map_or(0xC08040, |f| f.color);
+            self.draw_nest(nest, color, simulation.config().nests.spawn_cost);
+        }
+
+        self.draw_pack_links(simulation);
         
         for entity in entities {
-            self.draw_entity(entity);
+            self.draw_entity(entity, simulation.species(), factions);
         }
         
         self.draw_ui_info(entities);
+        self.draw_faction_bars(simulation);
     }
 
     /// Draw a subtle background pattern
@@ -56,18 +94,142 @@ impl Renderer {
         }
     }
 
-    /// Draw a single entity
-    fn draw_entity(&mut self, entity: &Entity) {
+    /// Draw a nest as a ring in its faction's colour, filled in proportion to
+    /// how close its store is to raising the next gatherer
+    fn draw_nest(&mut self, nest: &Nest, color: u32, spawn_cost: u32) {
+        let radius = nest.radius as i32;
+        let fullness = nest.food as f32 / spawn_cost.max(1) as f32;
+        let fill = (nest.radius * fullness.clamp(0.0, 1.0).sqrt()) as i32;
+        let (cx, cy) = nest.position.to_pixel();
+
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let distance_squared = dx * dx + dy * dy;
+                if distance_squared > radius * radius {
+                    continue;
+                }
+                if distance_squared >= (radius - 1) * (radius - 1) {
+                    self.set_pixel(cx + dx, cy + dy, color);
+                } else if distance_squared <= fill * fill {
+                    self.set_pixel(cx + dx, cy + dy, 0x604010);
+                }
+            }
+        }
+    }
+
+    /// Tint fertile patches of soil brown, darker the richer they are
+    fn draw_soil(&mut self, soil: &ScalarGrid) {
+        let cell_size = soil.cell_size() as i32;
+        for (corner, fertility) in soil.cells() {
+            if fertility < 0.5 {
+                continue;
+            }
+            let richness = (fertility / 40.0).min(1.0);
+            let red = (48.0 * richness) as u32;
+            let green = (32.0 * richness) as u32;
+            let color = (red << 16) | (green << 8) | 0x20;
+            let (left, top) = corner.to_pixel();
+            for y in top..top + cell_size {
+                for x in left..left + cell_size {
+                    self.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Tint cells by trail intensity: green for food trails, blue for home
+    /// trails, mixed where they overlap
+    fn draw_trails(&mut self, pheromones: &Pheromones) {
+        let cell_size = pheromones.food.cell_size() as i32;
+        for ((corner, food), (_, home)) in pheromones.food.cells().zip(pheromones.home.cells()) {
+            let green = ((food * 4.0).min(1.0) * 96.0) as u32;
+            let blue = ((home * 4.0).min(1.0) * 96.0) as u32;
+            if green < 8 && blue < 8 {
+                continue;
+            }
+            let (left, top) = corner.to_pixel();
+            for y in top..top + cell_size {
+                for x in left..left + cell_size {
+                    if let Some(pixel) = self.pixel(x, y) {
+                        let tinted = (pixel & 0xFF00FF) | ((pixel >> 8 & 0xFF).max(green) << 8);
+                        let tinted = (tinted & 0xFFFF00) | (tinted & 0xFF).max(blue);
+                        self.set_pixel(x, y, tinted);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draw an obstacle as a filled grey disc
+    fn draw_obstacle(&mut self, obstacle: &Obstacle) {
+        let radius = obstacle.radius as i32;
+        let (cx, cy) = obstacle.position.to_pixel();
+
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy <= radius * radius {
+                    let color = if dx * dx + dy * dy >= (radius - 2) * (radius - 2) {
+                        0x707070
+                    } else {
+                        0x404048
+                    };
+                    self.set_pixel(cx + dx, cy + dy, color);
+                }
+            }
+        }
+    }
+
+    /// Draw faint lines tying each pack member to its leader
+    fn draw_pack_links(&mut self, simulation: &Simulation) {
+        for pack in simulation.get_packs() {
+            let Some(leader) = simulation.entity_by_id(pack.leader) else { continue };
+            for member in pack.members.iter().filter_map(|id| simulation.entity_by_id(*id)) {
+                if member.id != leader.id {
+                    self.draw_line(member.position, leader.position, 0x602020);
+                }
+            }
+        }
+    }
+
+    /// Stipple claimed cells in their owner's colour, brighter the fresher
+    /// the marks: faction colours for factions, shades of red for predators
+    fn draw_territories(&mut self, territories: &Territories, factions: &[FactionConfig]) {
+        const PREDATOR_COLORS: [u32; 5] = [0xFF4040, 0xFF8040, 0xFF40A0, 0xC06040, 0xFFC040];
+        let cell_size = territories.cell_size() as i32;
+        for (corner, claim) in territories.claims() {
+            let color = match claim.owner {
+                Claimant::Faction(id) => factions.get(id).map_or(0xFFFFFF, |f| f.color),
+                Claimant::Predator(id) => PREDATOR_COLORS[id as usize % PREDATOR_COLORS.len()],
+            };
+            let color = dim(color, 0.2 + 0.8 * claim.strength.clamp(0.0, 1.0));
+            let (left, top) = corner.to_pixel();
+            for y in (top..top + cell_size).step_by(4) {
+                for x in (left..left + cell_size).step_by(4) {
+                    self.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// Draw a single entity, letting a custom species draw its own. Faction
+    /// members take their faction's colour, dimmed as their energy runs low.
+    fn draw_entity(&mut self, entity: &Entity, species: &SpeciesRegistry, factions: &[FactionConfig]) {
+        if species.get(entity.entity_type).is_some_and(|s| s.draw(entity, self)) {
+            return;
+        }
+
         let size = entity.size as i32;
         let half_size = size / 2;
+        let (cx, cy) = entity.position.to_pixel();
+        let color = match entity.faction.and_then(|id| factions.get(id)) {
+            Some(faction) => dim(faction.color, 0.3 + 0.7 * entity.energy as f32 / entity.max_energy as f32),
+            None => entity.color,
+        };
         
         for dy in -half_size..=half_size {
             for dx in -half_size..=half_size {
-                let x = entity.position.x + dx;
-                let y = entity.position.y + dy;
-                
                 if dx * dx + dy * dy <= half_size * half_size {
-                    self.set_pixel(Position::new(x, y), entity.color);
+                    self.set_pixel(cx + dx, cy + dy, color);
                 }
             }
         }
@@ -76,6 +238,42 @@ impl Renderer {
             EntityType::Gatherer => self.draw_gatherer_decoration(entity),
             EntityType::Resource => self.draw_resource_decoration(entity),
             EntityType::Predator => self.draw_predator_decoration(entity),
+            EntityType::Carcass => self.draw_carcass_decoration(entity),
+            EntityType::Scavenger | EntityType::Custom(_) => {}
+        }
+
+        if entity.carrying > 0 {
+            self.set_pixel(cx, cy, 0xFFFF00);
+        }
+
+        self.draw_health_marker(entity);
+    }
+
+    /// Mark exposed (pink), infected (magenta, with a ring) and recovered
+    /// (cyan) hosts with a dot above and to the right
+    fn draw_health_marker(&mut self, entity: &Entity) {
+        let color = match entity.health {
+            HealthState::Susceptible => return,
+            HealthState::Exposed { .. } => 0xFFA0E0,
+            HealthState::Infected { .. } => 0xFF00FF,
+            HealthState::Recovered { .. } => 0x40E0E0,
+        };
+        let (cx, cy) = entity.position.to_pixel();
+        let offset = entity.size as i32 + 2;
+        for dy in 0..2 {
+            for dx in 0..2 {
+                self.set_pixel(cx + offset + dx, cy - offset - dy, color);
+            }
+        }
+
+        if entity.health.is_infectious() {
+            let radius = entity.size as f32 + 4.0;
+            for angle in (0..12).step_by(2) {
+                let radians = angle as f32 * std::f32::consts::TAU / 12.0;
+                let x = cx + (radius * radians.cos()) as i32;
+                let y = cy + (radius * radians.sin()) as i32;
+                self.set_pixel(x, y, color);
+            }
         }
     }
 
@@ -84,25 +282,18 @@ impl Renderer {
         let energy_ratio = entity.energy as f32 / entity.max_energy as f32;
         let bar_width = 8;
         let bar_height = 2;
-        let bar_y = entity.position.y - entity.size as i32 - 3;
+        let (cx, cy) = entity.position.to_pixel();
+        let bar_y = cy - entity.size as i32 - 3;
         
         for x in 0..bar_width {
             for y in 0..bar_height {
-                let pos = Position::new(
-                    entity.position.x - bar_width / 2 + x,
-                    bar_y + y
-                );
-                self.set_pixel(pos, 0x404040);
+                self.set_pixel(cx - bar_width / 2 + x, bar_y + y, 0x404040);
             }
         }
         
         let fill_width = (bar_width as f32 * energy_ratio) as i32;
         for x in 0..fill_width {
             for y in 0..bar_height {
-                let pos = Position::new(
-                    entity.position.x - bar_width / 2 + x,
-                    bar_y + y
-                );
                 let color = if energy_ratio > 0.5 {
                     0x00FF00
                 } else if energy_ratio > 0.25 {
@@ -110,7 +301,18 @@ impl Renderer {
                 } else {
                     0xFF0000
                 };
-                self.set_pixel(pos, color);
+                self.set_pixel(cx - bar_width / 2 + x, bar_y + y, color);
+            }
+        }
+
+        // White outline marks a gatherer running from a predator
+        if entity.fleeing {
+            let ring_radius = entity.size as i32;
+            for angle in 0..12 {
+                let radians = (angle as f32) * std::f32::consts::PI * 2.0 / 12.0;
+                let x = cx + (ring_radius as f32 * radians.cos()) as i32;
+                let y = cy + (ring_radius as f32 * radians.sin()) as i32;
+                self.set_pixel(x, y, 0xFFFFFF);
             }
         }
     }
@@ -121,11 +323,12 @@ impl Renderer {
         
         if energy_ratio > 0.8 {
             let ring_radius = entity.size as i32 + 2;
+            let (cx, cy) = entity.position.to_pixel();
             for angle in 0..16 {
                 let radians = (angle as f32) * std::f32::consts::PI * 2.0 / 16.0;
-                let x = entity.position.x + (ring_radius as f32 * radians.cos()) as i32;
-                let y = entity.position.y + (ring_radius as f32 * radians.sin()) as i32;
-                self.set_pixel(Position::new(x, y), 0xFFFFAA);
+                let x = cx + (ring_radius as f32 * radians.cos()) as i32;
+                let y = cy + (ring_radius as f32 * radians.sin()) as i32;
+                self.set_pixel(x, y, 0xFFFFAA);
             }
         }
     }
@@ -133,11 +336,22 @@ impl Renderer {
     /// Draw predator-specific decoration (hunting indicator)
     fn draw_predator_decoration(&mut self, entity: &Entity) {
         let spike_length = entity.size as i32 + 1;
+        let (cx, cy) = entity.position.to_pixel();
         for angle in 0..8 {
             let radians = (angle as f32) * std::f32::consts::PI * 2.0 / 8.0;
-            let x = entity.position.x + (spike_length as f32 * radians.cos()) as i32;
-            let y = entity.position.y + (spike_length as f32 * radians.sin()) as i32;
-            self.set_pixel(Position::new(x, y), 0xFF4444);
+            let x = cx + (spike_length as f32 * radians.cos()) as i32;
+            let y = cy + (spike_length as f32 * radians.sin()) as i32;
+            self.set_pixel(x, y, 0xFF4444);
+        }
+    }
+
+    /// Draw carcass-specific decoration (a cross over the remains)
+    fn draw_carcass_decoration(&mut self, entity: &Entity) {
+        let arm = entity.size as i32 + 1;
+        let (cx, cy) = entity.position.to_pixel();
+        for offset in -arm..=arm {
+            self.set_pixel(cx + offset, cy + offset, 0x604020);
+            self.set_pixel(cx + offset, cy - offset, 0x604020);
         }
     }
 
@@ -146,50 +360,112 @@ impl Renderer {
         let gatherer_count = entities.iter().filter(|e| e.entity_type == EntityType::Gatherer).count();
         let resource_count = entities.iter().filter(|e| e.entity_type == EntityType::Resource).count();
         let predator_count = entities.iter().filter(|e| e.entity_type == EntityType::Predator).count();
+        let scavenger_count = entities.iter().filter(|e| e.entity_type == EntityType::Scavenger).count();
         
         for i in 0..gatherer_count.min(20) {
-            let pos = Position::new(10 + (i * 3) as i32, 10);
-            self.set_pixel(pos, 0x00FF00);
+            self.set_pixel(10 + (i * 3) as i32, 10, 0x00FF00);
         }
         
         for i in 0..resource_count.min(20) {
-            let pos = Position::new(10 + (i * 3) as i32, 15);
-            self.set_pixel(pos, 0xFFFF00);
+            self.set_pixel(10 + (i * 3) as i32, 15, 0xFFFF00);
         }
         
         for i in 0..predator_count.min(20) {
-            let pos = Position::new(10 + (i * 3) as i32, 20);
-            self.set_pixel(pos, 0xFF0000);
+            self.set_pixel(10 + (i * 3) as i32, 20, 0xFF0000);
+        }
+
+        for i in 0..scavenger_count.min(20) {
+            self.set_pixel(10 + (i * 3) as i32, 25, 0xA040FF);
+        }
+    }
+
+    /// Draw a pair of bars per faction along the top right: members above,
+    /// food stored in its nests below, side by side for comparison
+    fn draw_faction_bars(&mut self, simulation: &Simulation) {
+        let factions = &simulation.config().factions;
+        let spawn_cost = simulation.config().nests.spawn_cost.max(1);
+        for (index, faction) in factions.iter().enumerate() {
+            let members = simulation.get_entities().iter().filter(|e| e.faction == Some(index)).count();
+            let food: u32 = simulation.get_nests().iter().filter(|n| n.faction == Some(index)).map(|n| n.food).sum();
+            let left = self.width as i32 - 70 * (factions.len() - index) as i32;
+            for i in 0..members.min(20) {
+                self.set_pixel(left + (i * 3) as i32, 10, faction.color);
+            }
+            let stored = (food * 60 / spawn_cost).min(60) as i32;
+            for x in 0..stored {
+                self.set_pixel(left + x, 15, 0xC08040);
+            }
+        }
+    }
+
+    /// Draw a timeline along the bottom covering `span` seconds of simulated
+    /// time, filled up to `current` and marked there
+    pub fn draw_timeline(&mut self, span: (f32, f32), current: f32) {
+        let (left, right, top) = self.timeline_bounds();
+        let cursor = left + ((right - left) as f32 * fraction(span, current)) as i32;
+        for x in left..=right {
+            let color = if x <= cursor { 0x6070C0 } else { 0x303048 };
+            for y in top..top + TIMELINE_THICKNESS {
+                self.set_pixel(x, y, color);
+            }
+        }
+        for y in top - 3..top + TIMELINE_THICKNESS + 3 {
+            self.set_pixel(cursor, y, 0xFFFFFF);
+        }
+    }
+
+    /// Simulated time under a point on the timeline drawn for `span`, if the
+    /// point is on it
+    pub fn timeline_time_at(&self, x: f32, y: f32, span: (f32, f32)) -> Option<f32> {
+        let (left, right, top) = self.timeline_bounds();
+        let (x, y) = (x as i32, y as i32);
+        let on_timeline = x >= left && x <= right && y >= top - 4 && y < top + TIMELINE_THICKNESS + 4;
+        on_timeline.then(|| span.0 + (span.1 - span.0) * (x - left) as f32 / (right - left).max(1) as f32)
+    }
+
+    /// Left and right ends and top of the timeline in pixels
+    fn timeline_bounds(&self) -> (i32, i32, i32) {
+        let top = self.height as i32 - TIMELINE_MARGIN - TIMELINE_THICKNESS;
+        (TIMELINE_MARGIN, self.width as i32 - TIMELINE_MARGIN, top)
+    }
+
+    /// Read a pixel from the buffer, if it is on screen
+    fn pixel(&self, x: i32, y: i32) -> Option<u32> {
+        if x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32 {
+            Some(self.buffer[y as usize * self.width + x as usize])
+        } else {
+            None
         }
     }
 
     /// Set a pixel in the buffer - handles bounds checking
-    fn set_pixel(&mut self, position: Position, color: u32) {
-        if position.x >= 0 && position.x < self.width as i32 
-            && position.y >= 0 && position.y < self.height as i32 {
-            let index = (position.y as usize) * self.width + (position.x as usize);
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: u32) {
+        if x >= 0 && x < self.width as i32 
+            && y >= 0 && y < self.height as i32 {
+            let index = (y as usize) * self.width + (x as usize);
             if index < self.buffer.len() {
                 self.buffer[index] = color;
             }
         }
     }
 
-    /// Draw a line between two points (for debugging/visualization)
-    #[allow(dead_code)]
-    fn draw_line(&mut self, start: Position, end: Position, color: u32) {
-        let dx = (end.x - start.x).abs();
-        let dy = (end.y - start.y).abs();
-        let sx = if start.x < end.x { 1 } else { -1 };
-        let sy = if start.y < end.y { 1 } else { -1 };
+    /// Draw a line between two points
+    pub fn draw_line(&mut self, start: Position, end: Position, color: u32) {
+        let (start_x, start_y) = start.to_pixel();
+        let (end_x, end_y) = end.to_pixel();
+        let dx = (end_x - start_x).abs();
+        let dy = (end_y - start_y).abs();
+        let sx = if start_x < end_x { 1 } else { -1 };
+        let sy = if start_y < end_y { 1 } else { -1 };
         let mut err = dx - dy;
         
-        let mut x = start.x;
-        let mut y = start.y;
+        let mut x = start_x;
+        let mut y = start_y;
         
         loop {
-            self.set_pixel(Position::new(x, y), color);
+            self.set_pixel(x, y, color);
             
-            if x == end.x && y == end.y {
+            if x == end_x && y == end_y {
                 break;
             }
             
@@ -204,4 +480,19 @@ impl Renderer {
             }
         }
     }
-} 
\ No newline at end of file
+} 
+
+/// Scale each channel of a colour by a brightness between 0 and 1
+fn dim(color: u32, brightness: f32) -> u32 {
+    let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * brightness) as u32) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+/// How far `value` lies through `span`, from 0 to 1
+fn fraction(span: (f32, f32), value: f32) -> f32 {
+    if span.1 > span.0 {
+        ((value - span.0) / (span.1 - span.0)).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
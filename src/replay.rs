@@ -0,0 +1,163 @@
+use crate::config::SimulationConfig;
+use crate::scripting::ScriptHost;
+use crate::simulation::Simulation;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A change made to a running world from outside, such as by a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Intervention {
+    AddResources { count: usize },
+    AddGatherers { count: usize },
+    AddPredators { count: usize },
+}
+
+impl Intervention {
+    /// Make the change to a simulation
+    pub fn apply(&self, simulation: &mut Simulation) {
+        match *self {
+            Intervention::AddResources { count } => simulation.add_random_resources(count),
+            Intervention::AddGatherers { count } => simulation.add_random_gatherers(count),
+            Intervention::AddPredators { count } => simulation.add_random_predators(count),
+        }
+    }
+
+    /// What the change did, for the console
+    pub fn describe(&self) -> String {
+        match *self {
+            Intervention::AddResources { count } => format!("Added {} new resources", count),
+            Intervention::AddGatherers { count } => format!("Added {} new gatherers", count),
+            Intervention::AddPredators { count } => format!("Added {} new predator{}", count, if count == 1 { "" } else { "s" }),
+        }
+    }
+}
+
+/// An intervention and the tick it was made after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// Updates run before the intervention was made
+    pub tick: u64,
+    #[serde(flatten)]
+    pub intervention: Intervention,
+}
+
+/// Everything needed to re-run a session exactly: the seed, the world size,
+/// the settings with any brain files they refer to, the script's source, a
+/// fixed time step and every intervention with the tick it was made at.
+///
+/// The interventions are the ones made with the Space, G and P keys; the
+/// simulation has no brush or live parameter tools, so nothing else can
+/// change a recorded world from outside.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub world_width: usize,
+    pub world_height: usize,
+    /// Seconds of simulated time per update
+    pub time_step: f32,
+    /// Updates recorded
+    pub ticks: u64,
+    pub config: SimulationConfig,
+    /// Source of the script the run was recorded with, compiled from here on
+    /// playback so that later edits to the file can't change the replay
+    pub script: Option<String>,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    /// Start recording a simulation that has not been updated yet
+    pub fn record(simulation: &Simulation, time_step: f32) -> Self {
+        let (world_width, world_height) = simulation.world_size();
+        Replay {
+            seed: simulation.seed(),
+            world_width,
+            world_height,
+            time_step,
+            ticks: simulation.ticks(),
+            config: simulation.config().clone(),
+            script: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Keep a copy of the script running in the recorded world, and stop it
+    /// picking up edits to its file, which the replay could not repeat
+    pub fn keep_script(&mut self, scripts: &mut ScriptHost) {
+        scripts.set_hot_reload(false);
+        self.script = Some(scripts.source().to_string());
+    }
+
+    /// Compile the recorded script for a replayed world, without hot reload
+    pub fn scripts(&self, simulation: &mut Simulation) -> Result<Option<ScriptHost>, Box<dyn std::error::Error>> {
+        let Some(source) = &self.script else { return Ok(None) };
+        let path = self.config.scripting.file.clone().unwrap_or_else(|| "replay".into());
+        let mut scripts = ScriptHost::from_source(source.clone(), path, simulation)?;
+        scripts.set_hot_reload(false);
+        Ok(Some(scripts))
+    }
+
+    /// Note an intervention made to the simulation being recorded
+    pub fn push(&mut self, simulation: &Simulation, intervention: Intervention) {
+        self.events.push(ReplayEvent { tick: simulation.ticks(), intervention });
+        self.ticks = simulation.ticks();
+    }
+
+    /// Forget what was recorded after `tick`, to record a different future from there
+    pub fn truncate(&mut self, tick: u64) {
+        self.events.retain(|event| event.tick < tick);
+        self.ticks = tick;
+    }
+
+    /// The recorded world as it was before its first update
+    pub fn start(&self) -> Simulation {
+        Simulation::with_seed(self.world_width, self.world_height, self.config.clone(), self.seed)
+    }
+
+    /// Seconds of simulated time recorded
+    pub fn duration(&self) -> f32 {
+        self.ticks as f32 * self.time_step
+    }
+
+    /// Tick reached after `seconds` of simulated time
+    pub fn tick_at(&self, seconds: f32) -> u64 {
+        (seconds.max(0.0) / self.time_step).round() as u64
+    }
+
+    /// Re-run one update of the recording: make the interventions due at the
+    /// simulation's tick, then update it with the script hooks around it
+    pub fn step(&self, simulation: &mut Simulation, scripts: Option<&mut ScriptHost>) {
+        let tick = simulation.ticks();
+        let start = self.events.partition_point(|event| event.tick < tick);
+        for event in self.events[start..].iter().take_while(|event| event.tick == tick) {
+            event.intervention.apply(simulation);
+        }
+
+        match scripts {
+            Some(scripts) => {
+                scripts.before_update(simulation);
+                simulation.update(self.time_step);
+                scripts.after_update(simulation, self.time_step);
+            }
+            None => simulation.update(self.time_step),
+        }
+    }
+
+    /// Read a replay file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read replay {}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid replay file {}: {}", path.display(), e).into())
+    }
+
+    /// Write the replay as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
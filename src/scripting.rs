@@ -0,0 +1,685 @@
+use crate::brain::{BrainKind, SteeringPlan};
+use crate::config::FactionId;
+use crate::entity::{Entity, EntityId, EntityType, FLEE_SPEED_MULTIPLIER};
+use crate::nest::NestId;
+use crate::pack::PackId;
+use crate::position::{Position, Vec2};
+use crate::simulation::{SimEvent, Simulation};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the script file is checked for changes
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Changes a script asked for, applied once the hook returns
+type Commands = Rc<RefCell<Vec<Command>>>;
+
+#[derive(Debug, Clone)]
+enum Command {
+    Set { id: EntityId, field: Field, value: f32 },
+    Spawn { entity_type: EntityType, position: Position },
+    Remove(EntityId),
+}
+
+/// Entity fields scripts may write
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    X,
+    Y,
+    Vx,
+    Vy,
+    Energy,
+    MaxSpeed,
+}
+
+impl Field {
+    /// Name of the field in scripts
+    fn name(self) -> &'static str {
+        match self {
+            Field::X => "x",
+            Field::Y => "y",
+            Field::Vx => "vx",
+            Field::Vy => "vy",
+            Field::Energy => "energy",
+            Field::MaxSpeed => "max_speed",
+        }
+    }
+}
+
+/// A script's copy of an entity; writes update the copy and are applied to
+/// the real entity after the hook returns
+#[derive(Debug, Clone)]
+struct ScriptEntity {
+    id: EntityId,
+    entity_type: EntityType,
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    energy: u32,
+    max_energy: u32,
+    max_speed: f32,
+    age: f32,
+    size: u32,
+    fleeing: bool,
+    offspring: u32,
+    pack: Option<PackId>,
+    health: &'static str,
+    stage: &'static str,
+    lifespan: f32,
+    nest: Option<NestId>,
+    faction: Option<FactionId>,
+    carrying: u32,
+    commands: Commands,
+}
+
+impl ScriptEntity {
+    fn new(entity: &Entity, commands: &Commands) -> Self {
+        ScriptEntity {
+            id: entity.id,
+            entity_type: entity.entity_type,
+            x: entity.position.x,
+            y: entity.position.y,
+            vx: entity.velocity.x,
+            vy: entity.velocity.y,
+            energy: entity.energy,
+            max_energy: entity.max_energy,
+            max_speed: entity.max_speed,
+            age: entity.age,
+            size: entity.size,
+            fleeing: entity.fleeing,
+            offspring: entity.offspring,
+            pack: entity.pack_id,
+            health: entity.health.name(),
+            stage: entity.stage.name(),
+            lifespan: entity.lifespan,
+            nest: entity.nest_id,
+            faction: entity.faction,
+            carrying: entity.carrying,
+            commands: commands.clone(),
+        }
+    }
+
+    fn set(&mut self, field: Field, value: FLOAT) -> Result<(), Box<EvalAltResult>> {
+        let value = value as f32;
+        if !value.is_finite() {
+            return Err(format!("Entity {} must be a finite number, not {}", field.name(), value).into());
+        }
+        match field {
+            Field::X => self.x = value,
+            Field::Y => self.y = value,
+            Field::Vx => self.vx = value,
+            Field::Vy => self.vy = value,
+            Field::Energy => self.energy = value.clamp(0.0, self.max_energy as f32) as u32,
+            Field::MaxSpeed => self.max_speed = value.max(0.0),
+        }
+        self.commands.borrow_mut().push(Command::Set { id: self.id, field, value });
+        Ok(())
+    }
+}
+
+/// The simulation as scripts see it: a snapshot to read, plus requests to
+/// spawn and remove entities
+#[derive(Clone)]
+struct ScriptWorld {
+    entities: Rc<Vec<ScriptEntity>>,
+    commands: Commands,
+    rng: Rc<RefCell<StdRng>>,
+    memory: Rc<RefCell<Map>>,
+    /// Entity types scripts may name, including registered species
+    types: Rc<Vec<EntityType>>,
+    time: f32,
+    width: usize,
+    height: usize,
+}
+
+impl ScriptWorld {
+    fn entity_type(&self, name: &str) -> Result<EntityType, Box<EvalAltResult>> {
+        let entity_type = EntityType::from_name(name);
+        if self.types.contains(&entity_type) {
+            Ok(entity_type)
+        } else {
+            Err(format!("Unknown entity type '{}'", name).into())
+        }
+    }
+
+    fn entities_of(&self, entity_type: Option<EntityType>) -> Array {
+        self.entities
+            .iter()
+            .filter(|e| entity_type.is_none_or(|t| e.entity_type == t))
+            .cloned()
+            .map(Dynamic::from)
+            .collect()
+    }
+}
+
+/// What a script carries between calls: its random numbers and the values
+/// it `set`, saved with rewind keyframes
+#[derive(Clone)]
+pub struct ScriptState {
+    rng: StdRng,
+    memory: Map,
+}
+
+/// Runs a Rhai script's hooks against a simulation.
+///
+/// Scripts may define any of `on_tick(world, dt)`, `on_spawn(world, entity)`,
+/// `on_death(world, entity)`, `on_interaction(world, actor, target, kind)` and
+/// the behaviour functions named by `script` brains. Rhai has no file or
+/// network access, and each hook call is limited in how much work it may do.
+pub struct ScriptHost {
+    engine: Engine,
+    ast: AST,
+    path: PathBuf,
+    /// Text the current AST was compiled from
+    source: String,
+    hot_reload: bool,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    /// Behaviour function per entity type with a `script` brain
+    behaviours: Vec<(EntityType, String)>,
+    rng: Rc<RefCell<StdRng>>,
+    memory: Rc<RefCell<Map>>,
+    last_error: Option<String>,
+}
+
+impl ScriptHost {
+    /// Compile the script configured for a simulation and start recording its events
+    pub fn load(path: PathBuf, simulation: &mut Simulation) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = read_script(&path)?;
+        let host = Self::from_source(source, path, simulation)?;
+        println!("Loaded script {}", host.path.display());
+        Ok(host)
+    }
+
+    /// Compile script text for a simulation, naming it after `path` in
+    /// messages and watching that file for changes if hot reload is on
+    pub fn from_source(source: String, path: PathBuf, simulation: &mut Simulation) -> Result<Self, Box<dyn std::error::Error>> {
+        let settings = &simulation.config().scripting;
+        let mut engine = Engine::new();
+        engine.set_max_operations(settings.max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(10_000);
+        engine.set_max_array_size(10_000);
+        engine.set_max_map_size(10_000);
+        register_api(&mut engine);
+
+        let mut behaviours = Vec::new();
+        for (type_name, spec) in &simulation.config().brains {
+            if spec.kind != BrainKind::Script {
+                continue;
+            }
+            let entity_type = EntityType::from_name(type_name);
+            let function = spec.function.clone().unwrap_or_else(|| format!("behave_{}", type_name));
+            behaviours.push((entity_type, function));
+        }
+
+        let hot_reload = settings.hot_reload;
+        let ast = compile(&engine, &source, &path)?;
+        let mut host = ScriptHost {
+            engine,
+            ast,
+            modified: modified_time(&path),
+            path,
+            source,
+            hot_reload,
+            last_check: Instant::now(),
+            behaviours,
+            rng: Rc::new(RefCell::new(StdRng::seed_from_u64(simulation.seed()))),
+            memory: Rc::new(RefCell::new(Map::new())),
+            last_error: None,
+        };
+        host.loaded();
+        simulation.record_events(true);
+        Ok(host)
+    }
+
+    /// Text of the script currently running
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Turn picking up edits to the script file on or off
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+    }
+
+    /// Run before `Simulation::update`: pick up script edits and let
+    /// behaviour functions steer their entities
+    pub fn before_update(&mut self, simulation: &mut Simulation) {
+        self.check_reload();
+        if self.behaviours.is_empty() {
+            return;
+        }
+
+        let world = self.world(simulation);
+        for (entity_type, function) in self.behaviours.clone() {
+            let defined = self.defines(&function, 2);
+            for entity in world.entities.iter().filter(|e| e.entity_type == entity_type) {
+                let result = if defined { self.call(&function, (world.clone(), entity.clone())) } else { None };
+                match result.and_then(|value| plan_from(value, entity)) {
+                    Some(plan) => simulation.set_control(entity.id, plan),
+                    None => simulation.release_control(entity.id),
+                }
+            }
+        }
+        apply(&world.commands, simulation);
+    }
+
+    /// Run after `Simulation::update`: dispatch what happened to the event
+    /// hooks, then call `on_tick`
+    pub fn after_update(&mut self, simulation: &mut Simulation, delta_time: f32) {
+        let events = simulation.take_events();
+        let world = self.world(simulation);
+
+        // Entities that died this update, so interactions that killed them can still be reported
+        let departed: Vec<ScriptEntity> = events
+            .iter()
+            .filter_map(|event| match event {
+                SimEvent::Died(entity) => Some(ScriptEntity::new(entity, &world.commands)),
+                _ => None,
+            })
+            .collect();
+        let find = |id| world.entities.iter().chain(&departed).find(|e| e.id == id).cloned();
+
+        for event in &events {
+            match *event {
+                SimEvent::Spawned(id) => {
+                    if let Some(entity) = world.entities.iter().find(|e| e.id == id) {
+                        self.call_hook("on_spawn", (world.clone(), entity.clone()));
+                    }
+                }
+                SimEvent::Died(ref entity) => {
+                    if let Some(entity) = find(entity.id) {
+                        self.call_hook("on_death", (world.clone(), entity));
+                    }
+                }
+                SimEvent::Interaction { kind, actor, target } => {
+                    let (Some(actor), Some(target)) = (find(actor), find(target)) else { continue };
+                    self.call_hook("on_interaction", (world.clone(), actor, target, kind.name().to_string()));
+                }
+            }
+        }
+
+        self.call_hook("on_tick", (world.clone(), delta_time as FLOAT));
+        apply(&world.commands, simulation);
+    }
+
+    fn world(&self, simulation: &Simulation) -> ScriptWorld {
+        let commands = Commands::default();
+        let entities = simulation.get_entities().iter().map(|e| ScriptEntity::new(e, &commands)).collect();
+        let (width, height) = simulation.world_size();
+        ScriptWorld {
+            entities: Rc::new(entities),
+            commands,
+            rng: self.rng.clone(),
+            memory: self.memory.clone(),
+            types: Rc::new(simulation.entity_types()),
+            time: simulation.time(),
+            width,
+            height,
+        }
+    }
+
+    /// Whether the script defines a function taking this many arguments
+    fn defines(&self, name: &str, arity: usize) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name && f.params.len() == arity)
+    }
+
+    fn call_hook(&mut self, name: &str, args: impl rhai::FuncArgs) {
+        if self.defines(name, args_len(name)) {
+            self.call(name, args);
+        }
+    }
+
+    fn call(&mut self, name: &str, args: impl rhai::FuncArgs) -> Option<Dynamic> {
+        let options = CallFnOptions::new().eval_ast(false);
+        match self.engine.call_fn_with_options(options, &mut Scope::new(), &self.ast, name, args) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.report(format!("Script error in {}: {}", name, e));
+                None
+            }
+        }
+    }
+
+    /// Copy of what the script remembers between calls
+    pub fn save_state(&self) -> ScriptState {
+        ScriptState { rng: self.rng.borrow().clone(), memory: self.memory.borrow().clone() }
+    }
+
+    /// Go back to what the script remembered when `state` was saved
+    pub fn restore_state(&mut self, state: &ScriptState) {
+        *self.rng.borrow_mut() = state.rng.clone();
+        *self.memory.borrow_mut() = state.memory.clone();
+    }
+
+    /// Print an error unless it is the same one as last time
+    fn report(&mut self, message: String) {
+        if self.last_error.as_ref() != Some(&message) {
+            eprintln!("{}", message);
+            self.last_error = Some(message);
+        }
+    }
+
+    /// Recompile the script if the file changed, keeping the old one if the new one is broken
+    fn check_reload(&mut self) {
+        if !self.hot_reload || self.last_check.elapsed() < RELOAD_CHECK_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+
+        match read_script(&self.path).and_then(|source| Ok((compile(&self.engine, &source, &self.path)?, source))) {
+            Ok((ast, source)) => {
+                self.ast = ast;
+                self.source = source;
+                self.last_error = None;
+                self.loaded();
+                println!("Reloaded script {}", self.path.display());
+            }
+            Err(e) => self.report(e.to_string()),
+        }
+    }
+
+    /// Run the script's top-level statements and warn about missing behaviours
+    fn loaded(&mut self) {
+        if let Err(e) = self.engine.run_ast(&self.ast) {
+            self.report(format!("Script error in {}: {}", self.path.display(), e));
+        }
+        for (entity_type, function) in &self.behaviours {
+            if !self.defines(function, 2) {
+                eprintln!(
+                    "Script {} has no {}(world, entity); {} entities use their built-in behaviour",
+                    self.path.display(),
+                    function,
+                    entity_type.name()
+                );
+            }
+        }
+    }
+}
+
+/// Number of arguments each hook takes
+fn args_len(hook: &str) -> usize {
+    match hook {
+        "on_interaction" => 4,
+        _ => 2,
+    }
+}
+
+fn read_script(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    std::fs::read_to_string(path).map_err(|e| format!("Could not read script {}: {}", path.display(), e).into())
+}
+
+fn compile(engine: &Engine, source: &str, path: &Path) -> Result<AST, Box<dyn std::error::Error>> {
+    engine
+        .compile(source)
+        .map_err(|e| format!("Invalid script {}: {}", path.display(), e).into())
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Turn a behaviour function's result into steering. A map may give the
+/// desired velocity `vx`/`vy` and flags `wander`, `flee` and `reproduce`;
+/// anything else, such as `()`, hands the entity back to its built-in behaviour.
+fn plan_from(value: Dynamic, entity: &ScriptEntity) -> Option<SteeringPlan> {
+    let map = value.try_cast::<Map>()?;
+    let number = |key: &str| {
+        map.get(key)
+            .and_then(|v| v.as_float().ok().or_else(|| v.as_int().ok().map(|i| i as FLOAT)))
+            .unwrap_or(0.0) as f32
+    };
+    let flag = |key: &str| map.get(key).and_then(|v| v.as_bool().ok()).unwrap_or(false);
+
+    let fleeing = flag("flee");
+    let speed_limit = if fleeing { entity.max_speed * FLEE_SPEED_MULTIPLIER } else { entity.max_speed };
+    let force = if map.contains_key("vx") || map.contains_key("vy") {
+        Vec2::new(number("vx"), number("vy")).truncated(speed_limit) - Vec2::new(entity.vx, entity.vy)
+    } else {
+        Vec2::ZERO
+    };
+    Some(SteeringPlan {
+        wander: flag("wander"),
+        fleeing,
+        reproduce: flag("reproduce"),
+        ..SteeringPlan::steer(force, speed_limit)
+    })
+}
+
+/// Carry out what the scripts asked for
+fn apply(commands: &Commands, simulation: &mut Simulation) {
+    let (width, height) = simulation.world_size();
+    for command in commands.borrow_mut().drain(..) {
+        match command {
+            Command::Set { id, field, value } => {
+                let Some(entity) = simulation.entity_by_id_mut(id).filter(|_| value.is_finite()) else { continue };
+                match field {
+                    Field::X => entity.position.x = value,
+                    Field::Y => entity.position.y = value,
+                    Field::Vx => entity.velocity.x = value,
+                    Field::Vy => entity.velocity.y = value,
+                    Field::Energy => entity.energy = value.clamp(0.0, entity.max_energy as f32) as u32,
+                    Field::MaxSpeed => entity.max_speed = value.max(0.0),
+                }
+                entity.position.clamp_to_bounds(width, height);
+            }
+            Command::Spawn { entity_type, position } => {
+                simulation.spawn_entity(entity_type, position);
+            }
+            Command::Remove(id) => {
+                simulation.remove_entity(id);
+            }
+        }
+    }
+}
+
+/// Expose the entity and world types to scripts
+fn register_api(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<ScriptEntity>("Entity")
+        .register_get("id", |e: &mut ScriptEntity| e.id as INT)
+        .register_get("kind", |e: &mut ScriptEntity| e.entity_type.name().to_string())
+        .register_get("x", |e: &mut ScriptEntity| e.x as FLOAT)
+        .register_get("y", |e: &mut ScriptEntity| e.y as FLOAT)
+        .register_get("vx", |e: &mut ScriptEntity| e.vx as FLOAT)
+        .register_get("vy", |e: &mut ScriptEntity| e.vy as FLOAT)
+        .register_get("energy", |e: &mut ScriptEntity| e.energy as INT)
+        .register_get("max_energy", |e: &mut ScriptEntity| e.max_energy as INT)
+        .register_get("max_speed", |e: &mut ScriptEntity| e.max_speed as FLOAT)
+        .register_get("age", |e: &mut ScriptEntity| e.age as FLOAT)
+        .register_get("size", |e: &mut ScriptEntity| e.size as INT)
+        .register_get("fleeing", |e: &mut ScriptEntity| e.fleeing)
+        .register_get("offspring", |e: &mut ScriptEntity| e.offspring as INT)
+        .register_get("pack", |e: &mut ScriptEntity| e.pack.map_or(-1, |id| id as INT))
+        .register_get("health", |e: &mut ScriptEntity| e.health.to_string())
+        .register_get("stage", |e: &mut ScriptEntity| e.stage.to_string())
+        .register_get("lifespan", |e: &mut ScriptEntity| e.lifespan as FLOAT)
+        .register_get("nest", |e: &mut ScriptEntity| e.nest.map_or(-1, |id| id as INT))
+        .register_get("faction", |e: &mut ScriptEntity| e.faction.map_or(-1, |id| id as INT))
+        .register_get("carrying", |e: &mut ScriptEntity| e.carrying as INT)
+        .register_set("x", |e: &mut ScriptEntity, v: FLOAT| e.set(Field::X, v))
+        .register_set("y", |e: &mut ScriptEntity, v: FLOAT| e.set(Field::Y, v))
+        .register_set("vx", |e: &mut ScriptEntity, v: FLOAT| e.set(Field::Vx, v))
+        .register_set("vy", |e: &mut ScriptEntity, v: FLOAT| e.set(Field::Vy, v))
+        .register_set("energy", |e: &mut ScriptEntity, v: INT| e.set(Field::Energy, v as FLOAT))
+        .register_set("max_speed", |e: &mut ScriptEntity, v: FLOAT| e.set(Field::MaxSpeed, v))
+        .register_fn("distance_to", |e: &mut ScriptEntity, other: ScriptEntity| {
+            ((other.x - e.x).hypot(other.y - e.y)) as FLOAT
+        })
+        .register_fn("to_string", |e: &mut ScriptEntity| format!("{} {}", e.entity_type.name(), e.id));
+
+    engine
+        .register_type_with_name::<ScriptWorld>("World")
+        .register_get("time", |w: &mut ScriptWorld| w.time as FLOAT)
+        .register_get("width", |w: &mut ScriptWorld| w.width as INT)
+        .register_get("height", |w: &mut ScriptWorld| w.height as INT)
+        .register_fn("entities", |w: &mut ScriptWorld| w.entities_of(None))
+        .register_fn("entities", |w: &mut ScriptWorld, kind: &str| -> Result<Array, Box<EvalAltResult>> {
+            let entity_type = w.entity_type(kind)?;
+            Ok(w.entities_of(Some(entity_type)))
+        })
+        .register_fn("entity", |w: &mut ScriptWorld, id: INT| {
+            w.entities.iter().find(|e| e.id as INT == id).cloned().map_or(Dynamic::UNIT, Dynamic::from)
+        })
+        .register_fn("count", |w: &mut ScriptWorld, kind: &str| -> Result<INT, Box<EvalAltResult>> {
+            let entity_type = w.entity_type(kind)?;
+            Ok(w.entities.iter().filter(|e| e.entity_type == entity_type).count() as INT)
+        })
+        .register_fn("spawn_entity", |w: &mut ScriptWorld, kind: &str, x: FLOAT, y: FLOAT| -> Result<(), Box<EvalAltResult>> {
+            let entity_type = w.entity_type(kind)?;
+            let position = Position::new(x as f32, y as f32);
+            if !(position.x.is_finite() && position.y.is_finite()) {
+                return Err(format!("Cannot spawn a {} at ({}, {})", kind, x, y).into());
+            }
+            w.commands.borrow_mut().push(Command::Spawn { entity_type, position });
+            Ok(())
+        })
+        .register_fn("remove_entity", |w: &mut ScriptWorld, id: INT| {
+            w.commands.borrow_mut().push(Command::Remove(id as EntityId));
+        })
+        .register_fn("random", |w: &mut ScriptWorld| w.rng.borrow_mut().gen_range(0.0..1.0) as FLOAT)
+        .register_fn("random", |w: &mut ScriptWorld, low: FLOAT, high: FLOAT| -> Result<FLOAT, Box<EvalAltResult>> {
+            if !(high - low).is_finite() {
+                return Err(format!("random({}, {}) needs finite bounds less than the largest number apart", low, high).into());
+            }
+            Ok(if low < high { w.rng.borrow_mut().gen_range(low..high) } else { low })
+        })
+        .register_fn("get", |w: &mut ScriptWorld, key: &str| {
+            w.memory.borrow().get(key).cloned().unwrap_or(Dynamic::UNIT)
+        })
+        .register_fn("set", |w: &mut ScriptWorld, key: &str, value: Dynamic| {
+            w.memory.borrow_mut().insert(key.into(), value);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+
+    fn world() -> Simulation {
+        Simulation::with_seed(400, 300, SimulationConfig { quiet: true, ..SimulationConfig::default() }, 1)
+    }
+
+    fn host(source: &str, simulation: &mut Simulation) -> ScriptHost {
+        ScriptHost::from_source(source.to_string(), PathBuf::from("test.rhai"), simulation).unwrap()
+    }
+
+    fn update(scripts: &mut ScriptHost, simulation: &mut Simulation) {
+        scripts.before_update(simulation);
+        simulation.update(1.0 / 60.0);
+        scripts.after_update(simulation, 1.0 / 60.0);
+    }
+
+    fn remembered(scripts: &ScriptHost, key: &str) -> Option<INT> {
+        scripts.memory.borrow().get(key).and_then(|value| value.as_int().ok())
+    }
+
+    const COUNTERS: &str = r#"
+        fn count(world, key) {
+            let n = world.get(key);
+            world.set(key, if type_of(n) == "()" { 1 } else { n + 1 });
+        }
+        fn on_tick(world, dt) { count(world, "ticks"); }
+        fn on_spawn(world, entity) {
+            count(world, "spawns");
+            entity.energy = 1;
+        }
+        fn on_death(world, entity) { count(world, "deaths"); }
+    "#;
+
+    #[test]
+    fn hooks_hear_about_ticks_spawns_and_deaths() {
+        let mut simulation = world();
+        let mut scripts = host(COUNTERS, &mut simulation);
+        let victim = simulation.get_entities().iter().find(|e| e.entity_type == EntityType::Gatherer).unwrap().id;
+        simulation.entity_by_id_mut(victim).unwrap().energy = 0;
+        let newborn = simulation.spawn_entity(EntityType::Gatherer, Position::new(50.0, 50.0));
+        update(&mut scripts, &mut simulation);
+
+        assert_eq!(remembered(&scripts, "ticks"), Some(1));
+        assert!(remembered(&scripts, "spawns") >= Some(1));
+        assert!(remembered(&scripts, "deaths") >= Some(1));
+        assert_eq!(simulation.entity_by_id(newborn).map(|e| e.energy), Some(1));
+        assert!(simulation.entity_by_id(victim).is_none());
+    }
+
+    #[test]
+    fn runaway_scripts_are_stopped() {
+        let mut simulation = world();
+        let mut scripts = host("fn on_tick(world, dt) { loop { } }", &mut simulation);
+        update(&mut scripts, &mut simulation);
+        assert!(scripts.last_error.as_deref().is_some_and(|e| e.contains("on_tick")));
+        assert_eq!(simulation.ticks(), 1);
+    }
+
+    #[test]
+    fn numbers_that_would_break_the_world_are_rejected() {
+        let mut simulation = world();
+        let mut scripts = host(
+            r#"fn on_tick(world, dt) {
+                let entity = world.entities("gatherer")[0];
+                world.set("id", entity.id);
+                entity.x = parse_float("NaN");
+            }"#,
+            &mut simulation,
+        );
+        update(&mut scripts, &mut simulation);
+        assert!(scripts.last_error.as_deref().is_some_and(|e| e.contains("finite")));
+        let id = remembered(&scripts, "id").unwrap() as EntityId;
+        assert!(simulation.entity_by_id(id).unwrap().position.x.is_finite());
+
+        for call in ["world.random(-1e308, 1e308)", "world.random(0.0, parse_float(\"inf\"))"] {
+            let mut scripts = host(&format!("fn on_tick(world, dt) {{ {}; }}", call), &mut simulation);
+            update(&mut scripts, &mut simulation);
+            assert!(scripts.last_error.as_deref().is_some_and(|e| e.contains("finite bounds")), "{}", call);
+        }
+        let mut scripts = host("fn on_tick(world, dt) { world.spawn_entity(\"resource\", 1e39, 0.0); }", &mut simulation);
+        update(&mut scripts, &mut simulation);
+        assert!(scripts.last_error.as_deref().is_some_and(|e| e.contains("Cannot spawn")));
+    }
+
+    #[test]
+    fn edits_are_picked_up_only_with_hot_reload() {
+        let path = std::env::temp_dir().join(format!("rustcolony-{}-reload.rhai", std::process::id()));
+        let version = |n: INT| format!("fn on_tick(world, dt) {{ world.set(\"version\", {}); }}", n);
+        std::fs::write(&path, version(1)).unwrap();
+
+        let mut config = SimulationConfig { quiet: true, ..SimulationConfig::default() };
+        config.scripting.hot_reload = true;
+        let mut simulation = Simulation::with_seed(400, 300, config, 1);
+        let mut scripts = ScriptHost::load(path.clone(), &mut simulation).unwrap();
+        update(&mut scripts, &mut simulation);
+        assert_eq!(remembered(&scripts, "version"), Some(1));
+
+        // Pretend the file changed a while ago, so the next update checks it
+        let edit = |scripts: &mut ScriptHost, n| {
+            std::fs::write(&path, version(n)).unwrap();
+            scripts.modified = None;
+            scripts.last_check = Instant::now() - RELOAD_CHECK_INTERVAL;
+        };
+        edit(&mut scripts, 2);
+        update(&mut scripts, &mut simulation);
+        assert_eq!(remembered(&scripts, "version"), Some(2));
+        assert_eq!(scripts.source(), version(2));
+
+        scripts.set_hot_reload(false);
+        edit(&mut scripts, 3);
+        update(&mut scripts, &mut simulation);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(remembered(&scripts, "version"), Some(2));
+    }
+}
@@ -1,80 +1,437 @@
-use crate::entity::{Entity, EntityId, EntityType};
-use crate::position::Position;
+use crate::brain::{BrainKind, Blackboard, SteeringPlan, WorldView};
+use crate::config::{FactionId, Preset, SimulationConfig};
+use crate::disease::{self, HealthState};
+use crate::energy::{EnergyFlow, EnergyLedger};
+use crate::entity::{Entity, EntityId, EntityType, FLEE_SPEED_MULTIPLIER};
+use crate::food_web::{self, APEX, HERBIVORE, MESOPREDATOR, PLANT};
+use crate::grid::ScalarGrid;
+use crate::interactions::{InteractionKind, InteractionTable};
+use crate::life::LifeStage;
+use crate::nest::{Nest, NestId};
+use crate::neural::{HallOfFame, NeuralNetwork};
+use crate::obstacle::Obstacle;
+use crate::pack::{Pack, PackId};
+use crate::pheromone::Pheromones;
+use crate::position::{Position, Vec2};
+use crate::species::{Species, SpeciesRegistry};
+use crate::stats::{Sample, Stats};
+use crate::steering;
+use crate::territory::{Claimant, Territories};
+use crate::utility::{self, UtilityAction, UtilityProfile};
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Distance at which gatherers start braking when arriving at food
+const ARRIVE_SLOWING_RADIUS: f32 = 25.0;
+
+/// Seconds between blows a gatherer can land on rival faction members
+const FIGHT_COOLDOWN: f32 = 1.0;
+
+/// Personal space kept between predators
+const PREDATOR_SEPARATION_RADIUS: f32 = 24.0;
+
+/// Random spots tried when looking for one clear of obstacles
+const PLACEMENT_ATTEMPTS: usize = 100;
+
+/// Largest share of the world random obstacles may cover
+const MAX_OBSTACLE_COVERAGE: f32 = 0.5;
+
+/// What a brain decided for one entity this tick
+struct Decision {
+    index: usize,
+    plan: SteeringPlan,
+    blackboard: Option<Blackboard>,
+    utility_action: Option<UtilityAction>,
+}
+
+/// Something that happened during an update, recorded for observers such as scripts
+#[derive(Debug, Clone)]
+pub enum SimEvent {
+    Spawned(EntityId),
+    /// An entity died or was removed; carries its final state
+    Died(Box<Entity>),
+    Interaction { kind: InteractionKind, actor: EntityId, target: EntityId },
+}
 
 /// Core simulation struct
+#[derive(Clone)]
 pub struct Simulation {
     entities: Vec<Entity>,
+    obstacles: Vec<Obstacle>,
+    packs: Vec<Pack>,
+    nests: Vec<Nest>,
     world_width: usize,
     world_height: usize,
     next_entity_id: EntityId,
+    next_pack_id: PackId,
+    next_nest_id: NestId,
     simulation_time: f32,
+    /// Updates run so far
+    ticks: u64,
+    seed: u64,
     rng: StdRng,
     spawn_timer: f32,
     interaction_cooldown: f32,
+    config: SimulationConfig,
+    utility_profiles: BTreeMap<&'static str, UtilityProfile>,
+    hall_of_fame: HallOfFame,
+    /// Entities steered from outside, with the plan they were last given
+    controlled: BTreeMap<EntityId, SteeringPlan>,
+    record_events: bool,
+    events: Vec<SimEvent>,
+    species: SpeciesRegistry,
+    interactions: InteractionTable,
+    /// Fertility left in the soil by decomposed carcasses
+    soil: ScalarGrid,
+    soil_timer: f32,
+    stats: Stats,
+    pheromones: Pheromones,
+    territories: Territories,
+    territory_timer: f32,
+    ledger: EnergyLedger,
 }
 
 impl Simulation {
-    /// Create a new simulation with initial entities
-    pub fn new(world_width: usize, world_height: usize) -> Self {
+    /// Create a new simulation with initial entities and the given settings,
+    /// seeded from the config or randomly
+    pub fn with_config(world_width: usize, world_height: usize, config: SimulationConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        Self::with_seed(world_width, world_height, config, seed)
+    }
+
+    /// Create a new simulation whose randomness all derives from `seed`, so
+    /// equal seeds, settings and time steps give identical runs
+    pub fn with_seed(world_width: usize, world_height: usize, config: SimulationConfig, seed: u64) -> Self {
+        let utility_profiles = [EntityType::Gatherer, EntityType::Predator]
+            .into_iter()
+            .map(|entity_type| (entity_type.name(), config.utility_profile(entity_type)))
+            .collect();
+        let interactions = config.interaction_table();
+        let soil = ScalarGrid::new(world_width, world_height, config.carcasses.soil_cell_size);
+        let pheromones = Pheromones::new(world_width, world_height, config.pheromones.cell_size);
+        let territories = Territories::new(world_width, world_height, config.territory.cell_size);
+
         let mut simulation = Simulation {
             entities: Vec::new(),
+            obstacles: Vec::new(),
+            packs: Vec::new(),
+            nests: Vec::new(),
             world_width,
             world_height,
             next_entity_id: 1,
+            next_pack_id: 1,
+            next_nest_id: 1,
             simulation_time: 0.0,
-            rng: StdRng::from_entropy(),
+            ticks: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
             spawn_timer: 0.0,
             interaction_cooldown: 0.0,
+            config,
+            utility_profiles,
+            hall_of_fame: HallOfFame::default(),
+            controlled: BTreeMap::new(),
+            record_events: false,
+            events: Vec::new(),
+            species: SpeciesRegistry::default(),
+            interactions,
+            soil,
+            soil_timer: 0.0,
+            stats: Stats::default(),
+            pheromones,
+            territories,
+            territory_timer: 0.0,
+            ledger: EnergyLedger::default(),
         };
 
         simulation.initialize_world();
+        let sample = simulation.sample();
+        simulation.stats.record(sample);
         simulation
     }
 
     /// Initialize the world with starting entities
     fn initialize_world(&mut self) {
-        println!("Initializing world with starting entities...");
-        
-        for _ in 0..10 {
-            self.add_random_gatherers(1);
+        if !self.config.quiet {
+            println!("Initializing world with starting entities...");
         }
-        
-        for _ in 0..25 {
-            self.add_random_resources(1);
+
+        self.add_random_obstacles(self.config.obstacles);
+
+        match self.config.preset {
+            Preset::Classic => {
+                if self.config.factions.is_empty() {
+                    if self.config.nests.enabled {
+                        for _ in 0..self.config.nests.count {
+                            self.add_nest(None);
+                        }
+                    }
+
+                    for _ in 0..10 {
+                        self.add_random_gatherers(1);
+                    }
+                } else {
+                    for faction in 0..self.config.factions.len() {
+                        if self.config.nests.enabled {
+                            for _ in 0..self.config.factions[faction].nests {
+                                self.add_nest(Some(faction));
+                            }
+                        }
+                        self.add_faction_gatherers(faction, self.config.factions[faction].gatherers);
+                    }
+                }
+
+                for _ in 0..25 {
+                    self.add_random_resources(1);
+                }
+
+                for _ in 0..2 {
+                    self.add_random_predators(1);
+                }
+            }
+            Preset::FoodWeb => {
+                let registered = [
+                    self.register_species(food_web::Plant),
+                    self.register_species(food_web::Herbivore),
+                    self.register_species(food_web::Mesopredator),
+                    self.register_species(food_web::Apex),
+                ];
+                for result in registered {
+                    if let Err(e) = result {
+                        eprintln!("Food web: {}", e);
+                    }
+                }
+            }
         }
-        
-        for _ in 0..2 {
-            self.add_random_predators(1);
+
+        if self.config.carcasses.enabled {
+            self.add_random_scavengers(self.config.carcasses.initial_scavengers);
+        }
+
+        if self.config.disease.enabled {
+            let infected = self.infect_random(self.config.disease.initial_infected);
+            if !self.config.quiet {
+                println!("Disease introduced in {} hosts", infected);
+            }
         }
         
-        println!("World initialized with {} entities", self.entities.len());
+        if !self.config.quiet {
+            println!("World initialized with {} entities", self.entities.len());
+        }
     }
 
     /// Main update loop
     pub fn update(&mut self, delta_time: f32) {
         self.simulation_time += delta_time;
+        self.ticks += 1;
         self.spawn_timer += delta_time;
         self.interaction_cooldown -= delta_time;
+        let mut stock = self.begin_energy_audit();
 
         for entity in &mut self.entities {
-            entity.update(delta_time, self.world_width, self.world_height, &mut self.rng);
+            let before = entity.energy;
+            entity.update(delta_time);
+            if let Some(species) = self.species.get(entity.entity_type) {
+                species.update(entity, delta_time);
+            }
+            let producer = entity.entity_type == EntityType::Resource
+                || self.species.get(entity.entity_type).is_some_and(|s| s.draws_on_soil());
+            if self.config.energy.audited() {
+                let flow = if producer { EnergyFlow::Photosynthesis } else { EnergyFlow::Metabolism };
+                self.ledger.record(flow, entity.energy as f64 - before as f64);
+            }
         }
+        stock = self.audit(EnergyFlow::Metabolism, stock);
+
+        self.age_entities();
+        stock = self.audit(EnergyFlow::Death, stock);
+
+        self.decompose(delta_time);
+        stock = self.audit(EnergyFlow::Decomposition, stock);
+
+        self.spread_disease(delta_time);
+        stock = self.audit(EnergyFlow::Disease, stock);
+
+        self.update_packs();
 
         if self.interaction_cooldown <= 0.0 {
             self.handle_entity_interactions();
             self.interaction_cooldown = 0.05;
         }
+        stock = self.audit(EnergyFlow::Predation, stock);
+
+        self.update_nests();
+        stock = self.audit(EnergyFlow::Nests, stock);
+
+        self.update_pheromones(delta_time);
 
-        self.implement_smart_behaviors();
+        self.update_territories(delta_time);
+        stock = self.audit(EnergyFlow::Territory, stock);
+
+        self.implement_smart_behaviors(delta_time);
+        stock = self.audit(EnergyFlow::Reproduction, stock);
+
+        self.charge_metabolism(delta_time);
+        self.resolve_obstacle_collisions();
+        stock = self.audit(EnergyFlow::Movement, stock);
 
         self.remove_dead_entities();
+        stock = self.audit(EnergyFlow::Death, stock);
 
         if self.spawn_timer > 5.0 {
             self.spawn_periodic_entities();
             self.spawn_timer = 0.0;
         }
+        stock = self.audit(EnergyFlow::Spawning, stock);
+        self.end_energy_audit(stock, delta_time);
+
+        if self.stats.tick(delta_time, self.config.stats.interval) {
+            let sample = self.sample();
+            self.stats.record(sample);
+        }
+    }
+
+    /// All energy in the world: held by entities, carried or stored in nests,
+    /// and in the soil
+    pub fn energy_stock(&self) -> f64 {
+        let held: u64 = self.entities.iter().map(|e| e.stored_energy() as u64).sum();
+        let stored: u64 = self.nests.iter().map(|n| n.food as u64).sum();
+        held as f64 + stored as f64 + self.soil.total() as f64
+    }
+
+    /// Open this update's page of the energy ledger, returning the stock
+    fn begin_energy_audit(&mut self) -> f64 {
+        if !self.config.energy.audited() {
+            return 0.0;
+        }
+        let stock = self.energy_stock();
+        self.ledger.begin_tick(stock);
+        stock
+    }
+
+    /// Close a step of the update, charging the change in the energy stock
+    /// since `before` that the step didn't itemise to `flow`, and returning
+    /// the new stock
+    fn audit(&mut self, flow: EnergyFlow, before: f64) -> f64 {
+        if !self.config.energy.audited() {
+            return before;
+        }
+        let after = self.energy_stock();
+        self.ledger.checkpoint(flow, before, after);
+        after
+    }
+
+    /// Close this update's page of the ledger, and in strict mode stop the
+    /// run if any flow went the wrong way
+    fn end_energy_audit(&mut self, stock: f64, delta_time: f32) {
+        let settings = &self.config.energy;
+        if !settings.audited() {
+            return;
+        }
+        self.ledger.end_tick(stock, delta_time);
+        if settings.strict {
+            if let Err(problem) = self.ledger.check(settings.tolerance) {
+                panic!("Energy audit failed at {:.2}s: {}", self.simulation_time, problem);
+            }
+        }
+    }
+
+    /// Charge every mobile entity for staying alive and for how far and fast
+    /// it moved this step; entities pay it off in whole units as they update
+    fn charge_metabolism(&mut self, delta_time: f32) {
+        let settings = &self.config.metabolism;
+        for entity in self.entities.iter_mut().filter(|e| !e.entity_type.is_static() && e.max_speed > 0.0) {
+            let rate = settings.rate(entity.size, entity.velocity.length(), entity.max_speed);
+            entity.exertion += rate * entity.metabolism * delta_time;
+        }
+    }
+
+    /// Move entities with lifespans through their life stages, and let those
+    /// that have outlived them die of old age
+    fn age_entities(&mut self) {
+        let settings = &self.config.life;
+        if !settings.enabled {
+            return;
+        }
+
+        for entity in self.entities.iter_mut().filter(|e| e.lifespan > 0.0) {
+            entity.stage = LifeStage::at(entity.age, entity.lifespan, settings);
+            entity.metabolism = entity.stage.settings(settings).metabolism;
+            if entity.age >= entity.lifespan && !entity.is_dead() {
+                entity.energy = 0;
+                if !self.config.quiet {
+                    println!("{} {} died of old age at {:.1}s", entity.entity_type.name(), entity.id, entity.age);
+                }
+            }
+        }
+    }
+
+    /// Give a mobile entity a lifespan drawn from its type's bounds. Arrivals
+    /// from outside the world come in as adults of a random age; newborns
+    /// start at zero.
+    fn assign_lifespan(&mut self, entity: &mut Entity, arrival: bool) {
+        let settings = &self.config.life;
+        if !settings.enabled || entity.entity_type.is_static() || entity.max_speed <= 0.0 {
+            return;
+        }
+
+        let range = settings.lifespan_of(entity.entity_type);
+        entity.lifespan = if range.max > range.min {
+            self.rng.gen_range(range.min..range.max)
+        } else {
+            range.min
+        };
+        if arrival && settings.elder_from > settings.juvenile_until {
+            entity.age = entity.lifespan * self.rng.gen_range(settings.juvenile_until..settings.elder_from);
+        }
+        entity.stage = LifeStage::at(entity.age, entity.lifespan, settings);
+        entity.metabolism = entity.stage.settings(settings).metabolism;
+    }
+
+    /// Count populations, disease states and faction fortunes right now
+    pub fn sample(&self) -> Sample {
+        let mut sample = Sample { time: self.simulation_time, ..Default::default() };
+        for name in HealthState::NAMES {
+            sample.health.insert(name.to_string(), 0);
+        }
+        for faction in &self.config.factions {
+            sample.faction_members.insert(faction.name.clone(), 0);
+            sample.faction_food.insert(faction.name.clone(), 0);
+        }
+
+        for entity in &self.entities {
+            *sample.populations.entry(entity.entity_type.name().to_string()).or_default() += 1;
+            if entity.lifespan > 0.0 {
+                let key = format!("{}_{}", entity.entity_type.name(), entity.stage.name());
+                *sample.stages.entry(key).or_default() += 1;
+            }
+            if self.config.disease.hosts.contains(&entity.entity_type) {
+                *sample.health.entry(entity.health.name().to_string()).or_default() += 1;
+            }
+            if let Some(faction) = entity.faction.and_then(|id| self.config.factions.get(id)) {
+                *sample.faction_members.entry(faction.name.clone()).or_default() += 1;
+            }
+        }
+        for nest in &self.nests {
+            if let Some(faction) = nest.faction.and_then(|id| self.config.factions.get(id)) {
+                *sample.faction_food.entry(faction.name.clone()).or_default() += nest.food;
+            }
+        }
+        if self.config.territory.enabled {
+            for (claimant, cells) in self.territories.areas() {
+                let name = match claimant {
+                    Claimant::Faction(id) => self.config.factions[id].name.clone(),
+                    Claimant::Predator(_) => {
+                        sample.predator_claims += 1;
+                        "predators".to_string()
+                    }
+                };
+                *sample.territory.entry(name).or_default() += cells;
+            }
+        }
+        sample
     }
 
     /// Handle interactions between entities
@@ -87,165 +444,1343 @@ impl Simulation {
                 let entity_b = &self.entities[j];
                 
                 if entity_a.can_interact_with(entity_b) {
-                    interactions.push((i, j, entity_a.entity_type, entity_b.entity_type));
+                    interactions.push((i, j));
                 }
             }
         }
         
-        for (i, j, type_a, type_b) in interactions {
-            match (type_a, type_b) {
-                (EntityType::Gatherer, EntityType::Resource) => {
-                    let (left, right) = self.entities.split_at_mut(j);
-                    let gatherer = &mut left[i];
-                    let resource = &mut right[0];
-                    gatherer.consume_resource(resource);
+        // A hunter can only take one bite per pass, so prey packed into a herd
+        // dilutes each member's risk
+        let mut fed_hunters = Vec::new();
+        let mut kills = Vec::new();
+        let mut fed = Vec::new();
+        let now = self.simulation_time;
+
+        for (i, j) in interactions {
+            for (actor_index, target_index) in [(i, j), (j, i)] {
+                let (actor, target) = pair_mut(&mut self.entities, actor_index, target_index);
+                let Some(rule) = self.interactions.get(actor.entity_type, target.entity_type) else { continue };
+                if rule.kind == InteractionKind::Hunt && fed_hunters.contains(&actor_index) {
+                    continue;
+                }
+                let target_name = target.entity_type.name();
+                if actor.last_interactions.get(target_name).is_some_and(|last| now - last < rule.cooldown) {
+                    continue;
+                }
+
+                let before = actor.stored_energy() + target.stored_energy();
+                let target_energy = target.energy;
+                let Some(gained) = rule.apply(actor, target) else { continue };
+                if !self.config.quiet {
+                    let taken = target_energy - target.energy;
+                    match rule.kind {
+                        InteractionKind::Hunt => println!(
+                            "{:?} {} hunted {:?} {} for {} energy",
+                            actor.entity_type, actor.id, target.entity_type, target.id, taken
+                        ),
+                        InteractionKind::Consume => println!(
+                            "{:?} {} consumed {} energy from {:?} {} (now has {} energy)",
+                            actor.entity_type, actor.id, taken, target.entity_type, target.id, actor.energy
+                        ),
+                        _ => {}
+                    }
+                }
+                let flow = match rule.kind {
+                    InteractionKind::Compete => EnergyFlow::Competition,
+                    InteractionKind::Mutualism => EnergyFlow::Mutualism,
+                    _ => EnergyFlow::Predation,
+                };
+                if self.config.energy.audited() {
+                    let after = actor.stored_energy() + target.stored_energy();
+                    self.ledger.record(flow, after as f64 - before as f64);
+                }
+                actor.last_interactions.insert(target_name, now);
+                if rule.kind == InteractionKind::Hunt {
+                    fed_hunters.push(actor_index);
+                    kills.push((actor.id, gained));
                 }
-                (EntityType::Resource, EntityType::Gatherer) => {
-                    let (left, right) = self.entities.split_at_mut(j);
-                    let resource = &mut left[i];
-                    let gatherer = &mut right[0];
-                    gatherer.consume_resource(resource);
+                fed.push((rule.kind, actor.id, target.id));
+            }
+
+            let (a, b) = pair_mut(&mut self.entities, i, j);
+            if let (Some(faction_a), Some(faction_b)) = (a.faction, b.faction) {
+                if faction_a != faction_b {
+                    let before = a.energy + b.energy;
+                    let factions = &self.config.factions;
+                    let mut blows = Vec::new();
+                    if fight(a, b, factions[faction_a].aggression, now) {
+                        blows.push((a.id, b.id, factions[faction_a].aggression));
+                    }
+                    if fight(b, a, factions[faction_b].aggression, now) {
+                        blows.push((b.id, a.id, factions[faction_b].aggression));
+                    }
+                    for (attacker, defender, damage) in blows {
+                        if !self.config.quiet {
+                            println!("Gatherer {} attacked rival {} for {} energy", attacker, defender, damage);
+                        }
+                        fed.push((InteractionKind::Other("fight"), attacker, defender));
+                    }
+                    if self.config.energy.audited() {
+                        self.ledger.record(EnergyFlow::Fighting, (a.energy + b.energy) as f64 - before as f64);
+                    }
                 }
-                (EntityType::Predator, EntityType::Gatherer) => {
-                    let (left, right) = self.entities.split_at_mut(j);
-                    let predator = &mut left[i];
-                    let gatherer = &mut right[0];
-                    predator.hunt_gatherer(gatherer);
+            }
+            if let Some(kind) = self.species.get(a.entity_type).and_then(|s| s.interact(a, b)) {
+                fed.push((kind, a.id, b.id));
+            }
+            if let Some(kind) = self.species.get(b.entity_type).and_then(|s| s.interact(b, a)) {
+                fed.push((kind, b.id, a.id));
+            }
+        }
+
+        for (hunter_id, energy_gained) in kills {
+            self.share_kill(hunter_id, energy_gained);
+        }
+        for (kind, actor, target) in fed {
+            self.record(SimEvent::Interaction { kind, actor, target });
+        }
+    }
+
+    /// Let carcasses rot into the soil around them, and resources draw on the
+    /// soil's fertility to regrow faster
+    fn decompose(&mut self, delta_time: f32) {
+        let settings = &self.config.carcasses;
+
+        for carcass in self.entities.iter_mut().filter(|e| e.entity_type == EntityType::Carcass) {
+            if carcass.energy_consumption_timer < 1.0 {
+                continue;
+            }
+            carcass.energy_consumption_timer = 0.0;
+            let decayed = carcass.energy.min(settings.decay_rate);
+            carcass.energy -= decayed;
+            self.soil
+                .spread(&carcass.position, decayed as f32 * settings.decomposition_efficiency);
+        }
+
+        self.soil_timer += delta_time;
+        if self.soil_timer < 1.0 {
+            return;
+        }
+        self.soil_timer = 0.0;
+
+        let species = &self.species;
+        let producers = self.entities.iter_mut().filter(|e| {
+            e.entity_type == EntityType::Resource || species.get(e.entity_type).is_some_and(|s| s.draws_on_soil())
+        });
+        for resource in producers {
+            let room = (resource.max_energy - resource.energy) as f32;
+            let uptake = self.soil.get(&resource.position).min(settings.fertility_uptake).min(room).floor();
+            if uptake > 0.0 {
+                resource.energy += self.soil.take(&resource.position, uptake) as u32;
+            }
+        }
+    }
+
+    /// Run the course of every infection and pass the disease on between
+    /// touching hosts
+    fn spread_disease(&mut self, delta_time: f32) {
+        let settings = &self.config.disease;
+        if !settings.enabled {
+            return;
+        }
+
+        for entity in &mut self.entities {
+            disease::progress(entity, settings, delta_time);
+        }
+
+        let chance = (1.0 - (-settings.transmission_rate * delta_time).exp()).clamp(0.0, 1.0) as f64;
+        let mut caught = Vec::new();
+        for carrier in self.entities.iter().filter(|e| e.health.is_infectious()) {
+            for (index, host) in self.entities.iter().enumerate() {
+                if host.health == HealthState::Susceptible
+                    && settings.hosts.contains(&host.entity_type)
+                    && !caught.contains(&index)
+                    && carrier.can_interact_with(host)
+                    && self.rng.gen_bool(chance)
+                {
+                    if !self.config.quiet {
+                        println!("{} {} caught the disease from {}", host.entity_type.name(), host.id, carrier.id);
+                    }
+                    caught.push(index);
                 }
-                (EntityType::Gatherer, EntityType::Predator) => {
-                    let (left, right) = self.entities.split_at_mut(j);
-                    let gatherer = &mut left[i];
-                    let predator = &mut right[0];
-                    predator.hunt_gatherer(gatherer);
+            }
+        }
+        for index in caught {
+            self.entities[index].health = disease::infection(settings);
+        }
+    }
+
+    /// Infect up to `count` random susceptible hosts, returning how many were infected
+    pub fn infect_random(&mut self, count: usize) -> usize {
+        let hosts = &self.config.disease.hosts;
+        let mut candidates: Vec<usize> = (0..self.entities.len())
+            .filter(|&i| hosts.contains(&self.entities[i].entity_type))
+            .filter(|&i| self.entities[i].health == HealthState::Susceptible)
+            .collect();
+
+        let mut infected = 0;
+        while infected < count && !candidates.is_empty() {
+            let index = candidates.swap_remove(self.rng.gen_range(0..candidates.len()));
+            self.entities[index].health = disease::infection(&self.config.disease);
+            infected += 1;
+        }
+        infected
+    }
+
+    /// Unload food carried into nests, feed hungry members at home and raise
+    /// new gatherers from the store
+    fn update_nests(&mut self) {
+        let settings = &self.config.nests;
+
+        for entity in &mut self.entities {
+            let Some(nest) = entity.nest_id.and_then(|id| self.nests.iter_mut().find(|n| n.id == id)) else {
+                continue;
+            };
+            if !nest.contains(&entity.position) {
+                continue;
+            }
+
+            if entity.carrying > 0 {
+                nest.food += entity.carrying;
+                if !self.config.quiet {
+                    println!("Gatherer {} brought {} food home to nest {} (store {})", entity.id, entity.carrying, nest.id, nest.food);
                 }
-                _ => {}
+                entity.carrying = 0;
+                entity.scent = 1.0;
+            }
+            if (entity.energy as f32) < entity.max_energy as f32 * settings.feed_below {
+                let meal = (entity.max_energy - entity.energy).min(nest.food);
+                entity.energy += meal;
+                nest.food -= meal;
+            }
+        }
+
+        let mut births = Vec::new();
+        for nest in &mut self.nests {
+            while settings.spawn_cost > 0 && nest.food >= settings.spawn_cost {
+                nest.food -= settings.spawn_cost;
+                nest.raised += 1;
+                births.push((nest.id, nest.faction, nest.position));
+            }
+        }
+        for (nest_id, faction, position) in births {
+            let cost = self.config.nests.spawn_cost;
+            let id = self.spawn(EntityType::Gatherer, position, faction, false);
+            let mut surplus = 0;
+            if let Some(gatherer) = self.entity_by_id_mut(id) {
+                gatherer.nest_id = Some(nest_id);
+                gatherer.energy = cost.min(gatherer.max_energy);
+                surplus = cost - gatherer.energy;
+            }
+            // Whatever the newborn can't hold stays in the store
+            if let Some(nest) = self.nests.iter_mut().find(|n| n.id == nest_id) {
+                nest.food += surplus;
+            }
+            if !self.config.quiet {
+                println!("Nest {} raised gatherer {}", nest_id, id);
             }
         }
     }
 
-    /// Implement smart behaviors for entities
-    fn implement_smart_behaviors(&mut self) {
-        let mut behavior_updates = Vec::new();
-        
-        for (index, entity) in self.entities.iter().enumerate() {
-            match entity.entity_type {
-                EntityType::Gatherer => {
-                    if let Some(target) = entity.find_closest_entity(&self.entities, EntityType::Resource) {
-                        let target_pos = target.position;
-                        behavior_updates.push((index, target_pos));
+    /// Let trails evaporate and spread, and nest members mark where they walk
+    fn update_pheromones(&mut self, delta_time: f32) {
+        let settings = &self.config.pheromones;
+        if !settings.enabled {
+            return;
+        }
+
+        self.pheromones.update(settings, delta_time);
+        for entity in &mut self.entities {
+            self.pheromones.lay(entity, settings, delta_time);
+        }
+    }
+
+    /// Let claims fade, owners mark the ground they patrol, and once a second
+    /// charge intruders standing in a rival's territory
+    fn update_territories(&mut self, delta_time: f32) {
+        let settings = &self.config.territory;
+        if !settings.enabled {
+            return;
+        }
+
+        self.territories.decay(settings.decay_rate * delta_time);
+        let marks: Vec<(Position, Claimant)> = self
+            .entities
+            .iter()
+            .filter_map(|e| self.claimant(e).map(|claimant| (e.position, claimant)))
+            .collect();
+        for (position, claimant) in &marks {
+            self.territories.mark(position, *claimant, settings.mark_rate * delta_time);
+        }
+
+        self.territory_timer += delta_time;
+        if self.territory_timer < 1.0 {
+            return;
+        }
+        self.territory_timer -= 1.0;
+        let intruders: Vec<EntityId> = self
+            .entities
+            .iter()
+            .filter(|e| {
+                self.claimant(e)
+                    .is_some_and(|claimant| self.territories.is_foreign(&e.position, claimant, settings.respect_above))
+            })
+            .map(|e| e.id)
+            .collect();
+        let penalty = settings.intruder_penalty;
+        for entity in self.entities.iter_mut().filter(|e| intruders.contains(&e.id)) {
+            entity.energy = entity.energy.saturating_sub(penalty);
+        }
+    }
+
+    /// Who an entity marks territory for: its faction, or for a predator its
+    /// pack's leader or itself
+    fn claimant(&self, entity: &Entity) -> Option<Claimant> {
+        match entity.entity_type {
+            EntityType::Gatherer => entity.faction.map(Claimant::Faction),
+            EntityType::Predator => {
+                let pack = entity.pack_id.and_then(|id| self.packs.iter().find(|p| p.id == id));
+                Some(Claimant::Predator(pack.map_or(entity.id, |p| p.leader)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a hunter respects the ground at a position as a rival's
+    fn is_off_limits(&self, hunter: &Entity, position: &Position) -> bool {
+        let settings = &self.config.territory;
+        settings.enabled
+            && self
+                .claimant(hunter)
+                .is_some_and(|claimant| self.territories.is_foreign(position, claimant, settings.respect_above))
+    }
+
+    /// Nearest gatherer a predator can sense outside its rivals' territories
+    fn closest_prey<'a>(&'a self, hunter: &Entity) -> Option<&'a Entity> {
+        self.entities
+            .iter()
+            .filter(|e| e.entity_type == EntityType::Gatherer && hunter.can_sense(e, &self.obstacles))
+            .filter(|e| !self.is_off_limits(hunter, &e.position))
+            .min_by(|a, b| {
+                hunter
+                    .position
+                    .distance_squared_to(&a.position)
+                    .total_cmp(&hunter.position.distance_squared_to(&b.position))
+            })
+    }
+
+    /// A rival predator the hunter can sense trespassing on its own
+    /// well-marked ground, to be chased off
+    fn sensed_intruder<'a>(&'a self, hunter: &Entity) -> Option<&'a Entity> {
+        let settings = &self.config.territory;
+        let claimant = self.claimant(hunter).filter(|_| settings.enabled)?;
+        self.entities
+            .iter()
+            .filter(|e| e.entity_type == EntityType::Predator && hunter.can_sense(e, &self.obstacles))
+            .find(|e| {
+                self.claimant(e).is_some_and(|other| other.rivals(&claimant))
+                    && self
+                        .territories
+                        .claim_at(&e.position)
+                        .is_some_and(|claim| claim.owner == claimant && claim.strength >= settings.respect_above)
+            })
+    }
+
+    /// Place a nest for a faction somewhere clear of obstacles
+    pub fn add_nest(&mut self, faction: Option<FactionId>) -> NestId {
+        let id = self.next_nest_id;
+        self.next_nest_id += 1;
+        let position = self.random_position();
+        self.nests.push(Nest::new(id, position, self.config.nests.radius, faction));
+        id
+    }
+
+    /// Nest closest to a position, among a faction's nests if one is given
+    fn nearest_nest(&self, position: &Position, faction: Option<FactionId>) -> Option<&Nest> {
+        self.nests
+            .iter()
+            .filter(|nest| faction.is_none() || nest.faction == faction)
+            .min_by(|a, b| {
+                a.position
+                    .distance_squared_to(position)
+                    .total_cmp(&b.position.distance_squared_to(position))
+            })
+    }
+
+    /// Where a nest member should head instead of foraging: home, when it has
+    /// a full load, has food but nothing more in sight, or is hungry with
+    /// nothing in sight and food in store
+    fn homeward(&self, entity: &Entity, food_in_sight: bool) -> Option<Position> {
+        let nest = entity.nest_id.and_then(|id| self.nests.iter().find(|n| n.id == id))?;
+        let hungry = (entity.energy as f32) < entity.max_energy as f32 * self.config.nests.feed_below;
+        let going_home = entity.fully_loaded()
+            || (entity.carrying > 0 && !food_in_sight)
+            || (hungry && !food_in_sight && nest.food > 0);
+        going_home.then_some(nest.position)
+    }
+
+    /// Split the energy a pack member gained from a kill, as far as it still
+    /// holds it, with the pack-mates near it
+    fn share_kill(&mut self, hunter_id: EntityId, energy_gained: u32) {
+        let Some(hunter) = self.entity_by_id(hunter_id) else { return };
+        let Some(pack) = hunter.pack_id.and_then(|id| self.packs.iter().find(|p| p.id == id)) else {
+            return;
+        };
+
+        let hunter_position = hunter.position;
+        // Later bites and blows this pass may have cost the hunter some of its gain
+        let energy_gained = energy_gained.min(hunter.energy);
+        let share_radius = self.config.packs.share_radius;
+        let sharers: Vec<EntityId> = self
+            .entities
+            .iter()
+            .filter(|e| pack.contains(e.id))
+            .filter(|e| e.position.distance_to(&hunter_position) <= share_radius)
+            .map(|e| e.id)
+            .collect();
+        if sharers.len() < 2 {
+            return;
+        }
+
+        let share = energy_gained / sharers.len() as u32;
+        for entity in self.entities.iter_mut().filter(|e| sharers.contains(&e.id)) {
+            if entity.id == hunter_id {
+                entity.energy -= energy_gained - share;
+            } else {
+                entity.energy = (entity.energy + share).min(entity.max_energy);
+                entity.time_since_last_hunt = 0.0;
+            }
+        }
+    }
+
+    /// Form, maintain and dissolve predator packs, and pick each pack's target
+    fn update_packs(&mut self) {
+        if !self.config.packs.enabled {
+            self.packs.clear();
+            for entity in &mut self.entities {
+                entity.pack_id = None;
+            }
+            return;
+        }
+
+        let settings = self.config.packs.clone();
+
+        for pack in &mut self.packs {
+            pack.prune(&self.entities);
+        }
+        let entities = &self.entities;
+        self.packs.retain(|pack| {
+            if pack.members.len() < 2 {
+                return false;
+            }
+            if pack.average_hunger_time(entities) > settings.starvation_break_time {
+                if !self.config.quiet {
+                    println!("Pack {} broke up from starvation", pack.id);
+                }
+                return false;
+            }
+            true
+        });
+
+        for entity in &mut self.entities {
+            let still_member = entity
+                .pack_id
+                .is_some_and(|id| self.packs.iter().any(|p| p.id == id && p.contains(entity.id)));
+            if !still_member {
+                entity.pack_id = None;
+            }
+        }
+
+        // Lone, fed predators join the nearest predator within reach
+        let loners: Vec<usize> = (0..self.entities.len())
+            .filter(|&i| {
+                let e = &self.entities[i];
+                e.entity_type == EntityType::Predator
+                    && e.pack_id.is_none()
+                    && e.time_since_last_hunt < settings.starvation_break_time
+            })
+            .collect();
+        for i in loners {
+            if self.entities[i].pack_id.is_some() {
+                continue;
+            }
+            let position = self.entities[i].position;
+            let own_id = self.entities[i].id;
+            let partner = self
+                .entities
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.entity_type == EntityType::Predator && e.id != own_id)
+                .filter(|(_, e)| e.time_since_last_hunt < settings.starvation_break_time)
+                .filter(|(_, e)| e.position.distance_to(&position) <= settings.formation_radius)
+                .filter(|(_, e)| match e.pack_id {
+                    Some(id) => self.packs.iter().any(|p| p.id == id && p.members.len() < settings.max_size),
+                    None => true,
+                })
+                .min_by(|(_, a), (_, b)| {
+                    a.position.distance_to(&position).total_cmp(&b.position.distance_to(&position))
+                })
+                .map(|(index, _)| index);
+
+            let Some(j) = partner else { continue };
+            match self.entities[j].pack_id {
+                Some(pack_id) => {
+                    if let Some(pack) = self.packs.iter_mut().find(|p| p.id == pack_id) {
+                        pack.members.push(own_id);
+                        self.entities[i].pack_id = Some(pack_id);
+                        if !self.config.quiet {
+                            println!("Predator {} joined pack {}", own_id, pack_id);
+                        }
                     }
                 }
-                EntityType::Predator => {
-                    if let Some(target) = entity.find_closest_entity(&self.entities, EntityType::Gatherer) {
-                        let target_pos = target.position;
-                        behavior_updates.push((index, target_pos));
+                None => {
+                    let pack_id = self.next_pack_id;
+                    self.next_pack_id += 1;
+                    let other_id = self.entities[j].id;
+                    let leader = if self.entities[i].energy >= self.entities[j].energy { own_id } else { other_id };
+                    self.packs.push(Pack::new(pack_id, leader, vec![own_id, other_id]));
+                    self.entities[i].pack_id = Some(pack_id);
+                    self.entities[j].pack_id = Some(pack_id);
+                    if !self.config.quiet {
+                        println!("Predators {} and {} formed pack {} led by {}", own_id, other_id, pack_id, leader);
                     }
                 }
-                EntityType::Resource => {}
             }
         }
+
+        // Keep chasing the current target while anyone in the pack can sense it,
+        // otherwise take the prey nearest to the leader
+        let mut targets = Vec::new();
+        for pack in &self.packs {
+            let members: Vec<&Entity> = self.entities.iter().filter(|e| pack.contains(e.id)).collect();
+            let current = pack
+                .target
+                .and_then(|id| self.entity_by_id(id))
+                .filter(|target| members.iter().any(|m| m.can_sense(target, &self.obstacles)));
+
+            let target = current.or_else(|| {
+                let leader = self.entity_by_id(pack.leader)?;
+                members
+                    .iter()
+                    .filter_map(|m| self.closest_prey(m))
+                    .min_by(|a, b| {
+                        a.position
+                            .distance_to(&leader.position)
+                            .total_cmp(&b.position.distance_to(&leader.position))
+                    })
+            });
+            targets.push(target.map(|t| t.id));
+        }
+        for (pack, target) in self.packs.iter_mut().zip(targets) {
+            pack.target = target;
+        }
+    }
+
+    /// Implement smart behaviors for entities
+    fn implement_smart_behaviors(&mut self, delta_time: f32) {
+        let mut decisions = Vec::new();
+        let world = WorldView {
+            entities: &self.entities,
+            obstacles: &self.obstacles,
+            time: self.simulation_time,
+        };
         
-        for (index, target_pos) in behavior_updates {
-            if let Some(entity) = self.entities.get_mut(index) {
-                let move_probability = match entity.entity_type {
-                    EntityType::Gatherer => {
-                        let energy_ratio = entity.energy as f32 / entity.max_energy as f32;
-                        if energy_ratio < 0.3 {
-                            0.8
-                        } else if energy_ratio < 0.6 {
-                            0.4
-                        } else {
-                            0.2
-                        }
+        for (index, entity) in self.entities.iter().enumerate() {
+            if entity.entity_type.is_static() {
+                continue;
+            }
+
+            if let Some(plan) = self.controlled.get(&entity.id) {
+                decisions.push(Decision { index, plan: *plan, blackboard: None, utility_action: None });
+                continue;
+            }
+
+            let mut decision = Decision {
+                index,
+                plan: SteeringPlan::wander(Vec2::ZERO, 0.0),
+                blackboard: None,
+                utility_action: None,
+            };
+            let brain = self.config.brain_for(entity);
+            match brain.map(|spec| (spec.kind, spec.behavior_tree.as_ref())) {
+                Some((BrainKind::BehaviorTree, Some(tree))) => {
+                    let mut blackboard = entity.blackboard.clone();
+                    decision.plan = tree.tick(entity, &world, &mut blackboard);
+                    decision.blackboard = Some(blackboard);
+                }
+                Some((BrainKind::Neural, _)) if entity.network.is_some() => {
+                    if let Some(network) = &entity.network {
+                        decision.plan = network.decide(entity, &world);
                     }
-                    EntityType::Predator => {
-                        if entity.time_since_last_hunt > 15.0 {
-                            0.9
-                        } else if entity.time_since_last_hunt > 8.0 {
-                            0.6
-                        } else {
-                            0.4
+                }
+                Some((BrainKind::Utility, _)) if self.utility_profiles.contains_key(entity.entity_type.name()) => {
+                    let profile = &self.utility_profiles[entity.entity_type.name()];
+                    let action = profile.choose(entity, &world).unwrap_or(UtilityAction::Wander);
+                    decision.plan = utility::plan_action(action, entity, &world);
+                    decision.utility_action = Some(action);
+                }
+                _ => {
+                    decision.plan = match entity.entity_type {
+                        EntityType::Gatherer => self.plan_gatherer(entity),
+                        EntityType::Predator => self.plan_predator(entity),
+                        EntityType::Scavenger => self.plan_scavenger(entity),
+                        EntityType::Resource | EntityType::Carcass => continue,
+                        EntityType::Custom(_) => {
+                            match self.species.get(entity.entity_type).and_then(|s| s.plan(entity, &world)) {
+                                Some(plan) => plan,
+                                None => continue,
+                            }
                         }
-                    }
-                    _ => 0.0,
+                    };
+                }
+            }
+            decisions.push(decision);
+        }
+        
+        let mut parents = Vec::new();
+        for decision in decisions {
+            if let Some(entity) = self.entities.get_mut(decision.index) {
+                if let Some(blackboard) = decision.blackboard {
+                    entity.blackboard = blackboard;
+                }
+                if decision.utility_action.is_some() {
+                    entity.utility_action = decision.utility_action;
+                }
+
+                let plan = decision.plan;
+                let mut force = plan.force;
+                if plan.wander {
+                    force += steering::wander(entity, &mut self.rng);
+                }
+
+                let mut speed_limit = if entity.health.is_infectious() {
+                    plan.speed_limit * self.config.disease.speed_factor
+                } else {
+                    plan.speed_limit
                 };
-                
-                if self.rng.gen_bool(move_probability) {
-                    entity.position.move_towards(&target_pos, entity.speed);
-                    entity.position.clamp_to_bounds(self.world_width, self.world_height);
+                if entity.lifespan > 0.0 {
+                    speed_limit *= entity.stage.settings(&self.config.life).speed;
+                }
+
+                entity.fleeing = plan.fleeing;
+                entity.apply_steering(force, speed_limit, delta_time);
+                entity.bounce_off_bounds(self.world_width, self.world_height);
+
+                if plan.reproduce {
+                    parents.push(decision.index);
+                }
+            }
+        }
+
+        for index in parents {
+            self.reproduce(index);
+        }
+    }
+
+    /// Let an entity split off a child next to it, if it can afford to and
+    /// its life stage lets it
+    fn reproduce(&mut self, parent_index: usize) {
+        let parent = &self.entities[parent_index];
+        let fertility = if parent.lifespan > 0.0 {
+            parent.stage.settings(&self.config.life).fertility
+        } else {
+            1.0
+        };
+        if fertility <= 0.0 {
+            return;
+        }
+
+        let child_id = self.next_entity_id;
+        let offset = Vec2::from_angle(self.rng.gen_range(0.0..std::f32::consts::TAU)) * 6.0;
+        let parent = &mut self.entities[parent_index];
+        let mut child_position = parent.position.offset(offset);
+        child_position.clamp_to_bounds(self.world_width, self.world_height);
+
+        if let Some(mut child) = parent.reproduce(child_id, child_position) {
+            if let Some(species) = self.species.get(child.entity_type) {
+                let inherited = child.energy;
+                species.init(&mut child);
+                child.energy = inherited.min(child.max_energy);
+            }
+            if let Some(network) = &mut child.network {
+                let settings = &self.config.neural;
+                network.mutate(settings.mutation_rate, settings.mutation_strength, &mut self.rng);
+            }
+            parent.reproduction_cooldown /= fertility;
+            if !self.config.quiet {
+                println!("{:?} {} reproduced (child {} with {} energy)", parent.entity_type, parent.id, child_id, child.energy);
+            }
+            self.assign_lifespan(&mut child, false);
+            self.push_entity(child);
+        }
+    }
+
+    /// Gatherers blend foraging and herding with fear of any predator they,
+    /// or a herd-mate, can sense
+    fn plan_gatherer(&self, entity: &Entity) -> SteeringPlan {
+        let flocking = self.config.flocking_for(entity);
+        let food = entity.find_closest_entity(&self.entities, EntityType::Resource, &self.obstacles);
+        let energy_ratio = entity.energy as f32 / entity.max_energy as f32;
+
+        let gatherers = self.entities.iter().filter(|e| e.entity_type == EntityType::Gatherer);
+        let mut force = steering::separation(entity, gatherers.clone(), flocking.separation_radius)
+            * flocking.separation_weight;
+
+        let herd: Vec<&Entity> = if flocking.enabled {
+            gatherers
+                .filter(|e| e.id != entity.id && e.faction == entity.faction)
+                .filter(|e| entity.position.distance_to(&e.position) <= flocking.neighbour_radius)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        force += steering::alignment(entity, &herd) * flocking.alignment_weight;
+        force += steering::cohesion(entity, &herd) * flocking.cohesion_weight;
+
+        // Predators seen directly, then those only a herd-mate can see
+        let mut threats: Vec<(&Entity, f32)> = entity
+            .sensed_predators(&self.entities, &self.obstacles)
+            .into_iter()
+            .map(|predator| (predator, 1.0))
+            .collect();
+        for mate in &herd {
+            for predator in mate.sensed_predators(&self.entities, &self.obstacles) {
+                if !threats.iter().any(|(known, _)| known.id == predator.id) {
+                    threats.push((predator, flocking.alarm_fear));
                 }
             }
         }
+
+        if !threats.is_empty() {
+            let alarm_range = entity.sensing_radius + flocking.neighbour_radius;
+            let mut evasion = Vec2::ZERO;
+            let mut fear: f32 = 0.0;
+            for (predator, certainty) in threats {
+                let distance = entity.position.distance_to(&predator.position);
+                let proximity = (1.0 - (distance / alarm_range).min(1.0)) * certainty;
+                evasion += steering::evade(entity, predator) * proximity;
+                fear = fear.max(proximity);
+            }
+
+            let fear = fear * flocking.flee_weight;
+            let hunger = (1.0 - energy_ratio) * flocking.forage_weight;
+            force += evasion.normalized() * entity.max_speed * fear;
+            if let Some(food) = food {
+                force += steering::seek(entity, food.position) * hunger;
+            }
+
+            let fleeing = fear > hunger;
+            let speed_limit = if fleeing {
+                entity.max_speed * FLEE_SPEED_MULTIPLIER
+            } else {
+                entity.max_speed
+            };
+            return SteeringPlan { force, wander: false, speed_limit, fleeing, reproduce: false };
+        }
+
+        // Well-fed gatherers amble, hungry ones hurry
+        let urgency = if energy_ratio < 0.3 {
+            0.8
+        } else if energy_ratio < 0.6 {
+            0.4
+        } else {
+            0.2
+        };
+        let speed_limit = entity.max_speed * (0.4 + 0.6 * urgency);
+
+        if let Some(home) = self.homeward(entity, food.is_some()) {
+            force += steering::arrive(entity, home, ARRIVE_SLOWING_RADIUS) * flocking.forage_weight;
+            return SteeringPlan::steer(force, entity.max_speed * 0.7);
+        }
+
+        match food {
+            Some(food) => {
+                force += steering::arrive(entity, food.position, ARRIVE_SLOWING_RADIUS) * flocking.forage_weight;
+                SteeringPlan::steer(force, speed_limit)
+            }
+            None => {
+                force += self.trail_to_food(entity);
+                SteeringPlan::wander(force, speed_limit)
+            }
+        }
+    }
+
+    /// Pull up the food trail under a gatherer, if there is one to follow
+    fn trail_to_food(&self, entity: &Entity) -> Vec2 {
+        let settings = &self.config.pheromones;
+        if !settings.enabled {
+            return Vec2::ZERO;
+        }
+        let gradient = self.pheromones.food.gradient(&entity.position);
+        if gradient.length() < settings.min_gradient {
+            return Vec2::ZERO;
+        }
+        gradient.normalized() * entity.max_speed * settings.follow_weight
+    }
+
+    /// Predators pursue the nearest gatherer they can sense, or their pack's
+    /// shared target from an assigned flanking angle
+    fn plan_predator(&self, entity: &Entity) -> SteeringPlan {
+        // The longer since the last meal, the harder the chase
+        let urgency = if entity.time_since_last_hunt > 15.0 {
+            0.9
+        } else if entity.time_since_last_hunt > 8.0 {
+            0.6
+        } else {
+            0.4
+        };
+        let speed_limit = entity.max_speed * (0.4 + 0.6 * urgency);
+
+        let mut force = steering::separation(
+            entity,
+            self.entities.iter().filter(|e| e.entity_type == EntityType::Predator),
+            PREDATOR_SEPARATION_RADIUS,
+        ) * 0.5;
+
+        if let Some(pack) = entity.pack_id.and_then(|id| self.packs.iter().find(|p| p.id == id)) {
+            if let Some(pack_force) = self.plan_pack_member(entity, pack) {
+                force += pack_force;
+                return SteeringPlan::steer(force, speed_limit);
+            }
+        }
+
+        // Prey in a rival's territory is left alone; with nothing to hunt, a
+        // predator drives rivals off its own ground
+        match self.closest_prey(entity).or_else(|| self.sensed_intruder(entity)) {
+            Some(quarry) => {
+                force += steering::pursue(entity, quarry);
+                SteeringPlan::steer(force, speed_limit)
+            }
+            None => SteeringPlan::wander(force, speed_limit),
+        }
+    }
+
+    /// Scavengers keep clear of predators and otherwise make for the nearest
+    /// carcass they can smell
+    fn plan_scavenger(&self, entity: &Entity) -> SteeringPlan {
+        let threats = entity.sensed_predators(&self.entities, &self.obstacles);
+        if !threats.is_empty() {
+            let force = threats
+                .into_iter()
+                .map(|predator| steering::evade(entity, predator))
+                .fold(Vec2::ZERO, |sum, evasion| sum + evasion);
+            return SteeringPlan {
+                force,
+                wander: false,
+                speed_limit: entity.max_speed * FLEE_SPEED_MULTIPLIER,
+                fleeing: true,
+                reproduce: false,
+            };
+        }
+
+        let reproduce = entity.energy as f32 >= entity.max_energy as f32 * 0.9;
+        let plan = match entity.find_closest_entity(&self.entities, EntityType::Carcass, &self.obstacles) {
+            Some(carcass) => SteeringPlan::steer(
+                steering::arrive(entity, carcass.position, ARRIVE_SLOWING_RADIUS),
+                entity.max_speed,
+            ),
+            None => SteeringPlan::wander(Vec2::ZERO, entity.max_speed * 0.5),
+        };
+        SteeringPlan { reproduce, ..plan }
+    }
+
+    /// The leader drives straight at the shared target while followers spread
+    /// around it before closing in; without a target, followers trail the leader
+    fn plan_pack_member(&self, entity: &Entity, pack: &Pack) -> Option<Vec2> {
+        let leader = self.entity_by_id(pack.leader)?;
+        let Some(target) = pack.target.and_then(|id| self.entity_by_id(id)) else {
+            if entity.id == leader.id {
+                return None;
+            }
+            return Some(steering::arrive(entity, leader.position, ARRIVE_SLOWING_RADIUS));
+        };
+
+        let encircle_radius = self.config.packs.encircle_radius;
+        if entity.id == leader.id || entity.position.distance_to(&target.position) <= encircle_radius {
+            return Some(steering::pursue(entity, target));
+        }
+
+        let slot = pack.slot_of(entity.id)?;
+        let base_angle = target.position.vector_to(&leader.position).angle();
+        let angle = base_angle + std::f32::consts::TAU * (slot + 1) as f32 / pack.members.len() as f32;
+        let flank = target.position.offset(Vec2::from_angle(angle) * encircle_radius);
+        Some(steering::seek(entity, flank))
+    }
+
+    /// Keep mobile entities from walking through obstacles
+    fn resolve_obstacle_collisions(&mut self) {
+        for entity in &mut self.entities {
+            for obstacle in &self.obstacles {
+                obstacle.push_out(&mut entity.position);
+            }
+            entity.position.clamp_to_bounds(self.world_width, self.world_height);
+        }
     }
 
-    /// Remove dead entities
+    /// Remove dead entities, leaving carcasses where animals fell
     fn remove_dead_entities(&mut self) {
+        let mut carcasses = Vec::new();
+        for entity in self.entities.iter().filter(|e| e.is_dead()) {
+            self.hall_of_fame.consider(entity);
+            if self.record_events {
+                self.events.push(SimEvent::Died(Box::new(entity.clone())));
+            }
+            let leaves_carcass = entity.entity_type.leaves_carcass()
+                || self.species.get(entity.entity_type).is_some_and(|s| s.leaves_carcass());
+            if self.config.carcasses.enabled && leaves_carcass {
+                carcasses.push((entity.position, entity.size * self.config.carcasses.energy_per_size));
+            }
+        }
+
         let initial_count = self.entities.len();
         self.entities.retain(|entity| !entity.is_dead());
+        let entities = &self.entities;
+        self.controlled.retain(|id, _| entities.iter().any(|e| e.id == *id));
         let removed_count = initial_count - self.entities.len();
         
-        if removed_count > 0 {
+        if removed_count > 0 && !self.config.quiet {
             println!("Removed {} dead entities", removed_count);
         }
+
+        for (position, energy) in carcasses {
+            if energy > 0 {
+                if self.config.energy.audited() {
+                    self.ledger.record(EnergyFlow::Carcasses, energy as f64);
+                }
+                self.push_entity(Entity::new_carcass(0, position, energy));
+            }
+        }
     }
 
     /// Spawn new entities periodically
     fn spawn_periodic_entities(&mut self) {
+        match self.config.preset {
+            Preset::Classic => self.replenish_classic(),
+            Preset::FoodWeb => self.replenish_food_web(),
+        }
+
+        if self.config.carcasses.enabled
+            && self.count_entities_of_type(EntityType::Scavenger) == 0
+            && self.rng.gen_bool(0.1)
+        {
+            self.add_random_scavengers(1);
+            if !self.config.quiet {
+                println!("Spawned scavenger to clean up carcasses");
+            }
+        }
+    }
+
+    /// Keep gatherers and their food from dying out, and bring in the odd predator
+    fn replenish_classic(&mut self) {
         if self.count_entities_of_type(EntityType::Resource) < 30 {
             self.add_random_resources(3);
-            println!("Spawned 3 resources to maintain food supply");
+            if !self.config.quiet {
+                println!("Spawned 3 resources to maintain food supply");
+            }
         }
         
-        if self.count_entities_of_type(EntityType::Gatherer) < 3 {
-            self.add_random_gatherers(2);
-            println!("Spawned 2 gatherers to maintain population");
+        if self.config.factions.is_empty() {
+            if self.count_entities_of_type(EntityType::Gatherer) < 3 {
+                self.add_random_gatherers(2);
+                if !self.config.quiet {
+                    println!("Spawned 2 gatherers to maintain population");
+                }
+            }
+        } else {
+            for faction in 0..self.config.factions.len() {
+                let members = self.entities.iter().filter(|e| e.faction == Some(faction)).count();
+                if self.config.factions[faction].reinforce && members < 3 {
+                    self.add_faction_gatherers(faction, 2);
+                    if !self.config.quiet {
+                        println!("Spawned 2 gatherers to reinforce {}", self.config.factions[faction].name);
+                    }
+                }
+            }
         }
         
         if self.rng.gen_bool(0.15) && self.count_entities_of_type(EntityType::Predator) < 5 {
             self.add_random_predators(1);
-            println!("Spawned predator - survival depends on hunting success");
+            if !self.config.quiet {
+                println!("Spawned predator - survival depends on hunting success");
+            }
+        }
+    }
+
+    /// Reseed the food web's lower levels when they collapse, and let extinct
+    /// predators migrate back in now and then
+    fn replenish_food_web(&mut self) {
+        if self.count_entities_of_type(PLANT) < 15 {
+            self.add_random_entities(PLANT, 5);
+            if !self.config.quiet {
+                println!("Spawned 5 plants to reseed the food web");
+            }
+        }
+
+        if self.count_entities_of_type(HERBIVORE) < 4 {
+            self.add_random_entities(HERBIVORE, 3);
+            if !self.config.quiet {
+                println!("Spawned 3 herbivores to maintain population");
+            }
+        }
+
+        if self.count_entities_of_type(MESOPREDATOR) == 0 && self.rng.gen_bool(0.2) {
+            self.add_random_entities(MESOPREDATOR, 1);
+            if !self.config.quiet {
+                println!("A mesopredator migrated in");
+            }
+        }
+
+        if self.count_entities_of_type(APEX) == 0 && self.rng.gen_bool(0.1) {
+            self.add_random_entities(APEX, 1);
+            if !self.config.quiet {
+                println!("An apex predator migrated in");
+            }
         }
     }
 
     pub fn add_random_gatherers(&mut self, count: usize) {
         for _ in 0..count {
             let position = self.random_position();
-            let entity = Entity::new_gatherer(self.next_entity_id, position);
-            self.entities.push(entity);
-            self.next_entity_id += 1;
+            self.spawn_entity(EntityType::Gatherer, position);
         }
     }
 
     pub fn add_random_resources(&mut self, count: usize) {
         for _ in 0..count {
             let position = self.random_position();
-            let entity = Entity::new_resource(self.next_entity_id, position);
-            self.entities.push(entity);
-            self.next_entity_id += 1;
+            self.spawn_entity(EntityType::Resource, position);
         }
     }
 
     pub fn add_random_predators(&mut self, count: usize) {
         for _ in 0..count {
             let position = self.random_position();
-            let entity = Entity::new_predator(self.next_entity_id, position);
-            self.entities.push(entity);
-            self.next_entity_id += 1;
+            self.spawn_entity(EntityType::Predator, position);
+        }
+    }
+
+    pub fn add_random_scavengers(&mut self, count: usize) {
+        self.add_random_entities(EntityType::Scavenger, count);
+    }
+
+    /// Add entities of any type at random positions
+    pub fn add_random_entities(&mut self, entity_type: EntityType, count: usize) {
+        for _ in 0..count {
+            let position = self.random_position();
+            self.spawn_entity(entity_type, position);
+        }
+    }
+
+    /// Add gatherers of a faction at random positions
+    pub fn add_faction_gatherers(&mut self, faction: FactionId, count: usize) {
+        for _ in 0..count {
+            let position = self.random_position();
+            self.spawn_in_faction(EntityType::Gatherer, position, Some(faction));
+        }
+    }
+
+    /// Add a fresh entity of a type at a position, with a random heading and,
+    /// if its type uses them, a neural network. Gatherers join the nearest
+    /// nest and that nest's faction.
+    pub fn spawn_entity(&mut self, entity_type: EntityType, position: Position) -> EntityId {
+        self.spawn_in_faction(entity_type, position, None)
+    }
+
+    /// Add a fresh entity like `spawn_entity`, with gatherers joining a given
+    /// faction and its nearest nest
+    pub fn spawn_in_faction(&mut self, entity_type: EntityType, position: Position, faction: Option<FactionId>) -> EntityId {
+        self.spawn(entity_type, position, faction, true)
+    }
+
+    /// Add a fresh entity, as an adult of a random age if it is arriving from
+    /// outside the world or as a newborn otherwise
+    fn spawn(&mut self, entity_type: EntityType, position: Position, faction: Option<FactionId>, arrival: bool) -> EntityId {
+        let mut entity = Entity::new_of_type(entity_type, self.next_entity_id, position);
+        if let Some(species) = self.species.get(entity_type) {
+            species.init(&mut entity);
+        }
+        entity.position.clamp_to_bounds(self.world_width, self.world_height);
+        if entity_type == EntityType::Gatherer {
+            let nest = self.nearest_nest(&entity.position, faction);
+            entity.nest_id = nest.map(|n| n.id);
+            entity.faction = faction.or(nest.and_then(|n| n.faction));
+            if entity.nest_id.is_some() {
+                entity.carry_capacity = self.config.nests.carry_capacity;
+            }
+            if let Some(settings) = entity.faction.and_then(|id| self.config.factions.get(id)) {
+                entity.max_speed *= settings.speed_factor;
+            }
+        }
+        if !entity_type.is_static() {
+            entity.wander_heading = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            self.assign_network(&mut entity);
+            self.assign_lifespan(&mut entity, arrival);
+        }
+        self.push_entity(entity)
+    }
+
+    /// Add an entity under the next free id
+    fn push_entity(&mut self, mut entity: Entity) -> EntityId {
+        let id = self.next_entity_id;
+        self.next_entity_id += 1;
+        entity.id = id;
+        self.entities.push(entity);
+        self.record(SimEvent::Spawned(id));
+        id
+    }
+
+    /// Take an entity out of the world without it dying of natural causes
+    pub fn remove_entity(&mut self, id: EntityId) -> bool {
+        let Some(index) = self.entities.iter().position(|e| e.id == id) else { return false };
+        let entity = self.entities.remove(index);
+        self.controlled.remove(&id);
+        self.record(SimEvent::Died(Box::new(entity)));
+        true
+    }
+
+    /// Add a custom species and its initial population, returning the entity
+    /// type its members have
+    pub fn register_species(&mut self, species: impl Species + 'static) -> Result<EntityType, String> {
+        let initial_population = species.initial_population();
+        let entity_type = self.species.register(Arc::new(species))?;
+        self.utility_profiles
+            .insert(entity_type.name(), self.config.utility_profile(entity_type));
+
+        for _ in 0..initial_population {
+            let position = self.random_position();
+            self.spawn_entity(entity_type, position);
+        }
+        if !self.config.quiet {
+            println!("Registered species {} with {} entities", entity_type.name(), initial_population);
+        }
+        Ok(entity_type)
+    }
+
+    /// Custom species added with `register_species`
+    pub fn species(&self) -> &SpeciesRegistry {
+        &self.species
+    }
+
+    /// Built-in entity types followed by registered species
+    pub fn entity_types(&self) -> Vec<EntityType> {
+        EntityType::BUILTIN
+            .into_iter()
+            .chain(self.species.types())
+            .collect()
+    }
+
+    /// Add an entity that ignores its brain and follows plans given through
+    /// `set_control`; it stands still until the first one arrives
+    pub fn add_controlled_entity(&mut self, entity_type: EntityType) -> EntityId {
+        let position = self.random_position();
+        let id = self.spawn_entity(entity_type, position);
+        self.controlled.insert(id, SteeringPlan::steer(Vec2::ZERO, 0.0));
+        id
+    }
+
+    /// Take over an entity's steering, from the next update on, until it dies
+    /// or is released
+    pub fn set_control(&mut self, id: EntityId, plan: SteeringPlan) {
+        if self.entity_by_id(id).is_some() {
+            self.controlled.insert(id, plan);
+        }
+    }
+
+    /// Hand an entity back to its own brain
+    pub fn release_control(&mut self, id: EntityId) {
+        self.controlled.remove(&id);
+    }
+
+    /// Start or stop keeping `SimEvent`s for `take_events`
+    pub fn record_events(&mut self, enabled: bool) {
+        self.record_events = enabled;
+        if !enabled {
+            self.events.clear();
+        }
+    }
+
+    /// Events recorded since the last call
+    pub fn take_events(&mut self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn record(&mut self, event: SimEvent) {
+        if self.record_events {
+            self.events.push(event);
+        }
+    }
+
+    /// Give a fresh entity a network if its type uses neural brains: a mutated
+    /// copy of a seed champion when seeds were loaded, otherwise a random one
+    fn assign_network(&mut self, entity: &mut Entity) {
+        let Some(spec) = self.config.brain_for(entity) else { return };
+        if spec.kind != BrainKind::Neural {
+            return;
+        }
+
+        let settings = &self.config.neural;
+        let seeds: Vec<&NeuralNetwork> = spec
+            .seed_brains
+            .iter()
+            .flat_map(|hall| hall.of_type(entity.entity_type))
+            .map(|champion| &champion.network)
+            .collect();
+
+        entity.network = Some(if seeds.is_empty() {
+            NeuralNetwork::random(settings.hidden_neurons, &mut self.rng)
+        } else {
+            let mut network = seeds[self.rng.gen_range(0..seeds.len())].clone();
+            network.mutate(settings.mutation_rate, settings.mutation_strength, &mut self.rng);
+            network
+        });
+    }
+
+    /// Best networks seen so far, including those of entities still alive
+    pub fn best_brains(&self) -> HallOfFame {
+        let mut hall = self.hall_of_fame.clone();
+        for entity in &self.entities {
+            hall.consider(entity);
+        }
+        hall
+    }
+
+    pub fn add_random_obstacles(&mut self, count: usize) {
+        let world_area = (self.world_width * self.world_height) as f32;
+        let mut covered: f32 = self.obstacles.iter().map(|o| std::f32::consts::PI * o.radius * o.radius).sum();
+        for placed in 0..count {
+            let position = self.random_position();
+            let radius = self.rng.gen_range(15.0..40.0);
+            covered += std::f32::consts::PI * radius * radius;
+            if covered > world_area * MAX_OBSTACLE_COVERAGE {
+                if !self.config.quiet {
+                    println!("Placed {} of {} obstacles; more would cover over half the world", placed, count);
+                }
+                break;
+            }
+            self.obstacles.push(Obstacle::new(position, radius));
         }
     }
 
+    /// Pick a random position that is not inside an obstacle, or any position
+    /// if none turns up within a few tries; entities that land inside an
+    /// obstacle are pushed out of it on the next update
     fn random_position(&mut self) -> Position {
-        let x = self.rng.gen_range(10..(self.world_width as i32 - 10));
-        let y = self.rng.gen_range(10..(self.world_height as i32 - 10));
-        Position::new(x, y)
+        let mut position = Position::new(0.0, 0.0);
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let x = self.rng.gen_range(10.0..(self.world_width as f32 - 10.0));
+            let y = self.rng.gen_range(10.0..(self.world_height as f32 - 10.0));
+            position = Position::new(x, y);
+
+            if !self.obstacles.iter().any(|obstacle| obstacle.contains(&position)) {
+                break;
+            }
+        }
+        position
     }
 
     fn count_entities_of_type(&self, entity_type: EntityType) -> usize {
         self.entities.iter().filter(|e| e.entity_type == entity_type).count()
     }
 
+    /// Seed the simulation's randomness was drawn from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Settings the simulation was created with
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    /// Seconds of simulated time so far
+    pub fn time(&self) -> f32 {
+        self.simulation_time
+    }
+
+    /// Number of updates run so far
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Width and height of the world in pixels
+    pub fn world_size(&self) -> (usize, usize) {
+        (self.world_width, self.world_height)
+    }
+
     /// Get immutable reference to entities
     pub fn get_entities(&self) -> &[Entity] {
         &self.entities
     }
-} 
\ No newline at end of file
+
+    /// Look up a live entity by id
+    pub fn entity_by_id(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.iter().find(|e| e.id == id)
+    }
+
+    /// Look up a live entity by id for modification
+    pub fn entity_by_id_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.entities.iter_mut().find(|e| e.id == id)
+    }
+
+    /// Population and epidemic time series recorded so far
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Food and home trails laid by gatherers
+    pub fn pheromones(&self) -> &Pheromones {
+        &self.pheromones
+    }
+
+    /// Sources and sinks of energy so far
+    pub fn energy_ledger(&self) -> &EnergyLedger {
+        &self.ledger
+    }
+
+    /// Ground claimed by predators and factions
+    pub fn territories(&self) -> &Territories {
+        &self.territories
+    }
+
+    /// Fertility of the soil, fed by decomposing carcasses
+    pub fn soil(&self) -> &ScalarGrid {
+        &self.soil
+    }
+
+    /// Get immutable reference to nests
+    pub fn get_nests(&self) -> &[Nest] {
+        &self.nests
+    }
+
+    /// Get immutable reference to predator packs
+    pub fn get_packs(&self) -> &[Pack] {
+        &self.packs
+    }
+
+    /// Get immutable reference to obstacles
+    pub fn get_obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+} 
+
+/// Let a gatherer land a blow on a rival if its faction fights and it is
+/// ready to, returning whether it did
+fn fight(attacker: &mut Entity, defender: &mut Entity, damage: u32, now: f32) -> bool {
+    if damage == 0 || attacker.last_interactions.get("fight").is_some_and(|last| now - last < FIGHT_COOLDOWN) {
+        return false;
+    }
+    attacker.last_interactions.insert("fight", now);
+    defender.energy = defender.energy.saturating_sub(damage);
+    true
+}
+
+/// Mutable references to two different entities at once
+fn pair_mut(entities: &mut [Entity], a: usize, b: usize) -> (&mut Entity, &mut Entity) {
+    if a < b {
+        let (left, right) = entities.split_at_mut(b);
+        (&mut left[a], &mut right[0])
+    } else {
+        let (left, right) = entities.split_at_mut(a);
+        (&mut right[0], &mut left[b])
+    }
+}
@@ -0,0 +1,168 @@
+use crate::brain::{SteeringPlan, WorldView};
+use crate::entity::{Entity, EntityType};
+use crate::interactions::InteractionKind;
+use crate::renderer::Renderer;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Behaviour of an entity type added from outside the simulation.
+///
+/// Every hook has a default, so a species only implements what makes it
+/// different. Entities of the species start with neutral stats from
+/// `Entity::new_custom`, and can still be given a data-driven brain in the
+/// config under the species name.
+pub trait Species: Send + Sync {
+    /// Name used in config files and scripts; must not clash with a built-in type
+    fn name(&self) -> &'static str;
+
+    /// Set a fresh entity's starting stats and appearance
+    fn init(&self, _entity: &mut Entity) {}
+
+    /// Entities added when the species is registered
+    fn initial_population(&self) -> usize {
+        0
+    }
+
+    /// Per-update bookkeeping such as metabolism and colour
+    fn update(&self, _entity: &mut Entity, _delta_time: f32) {}
+
+    /// Movement for entities without a configured brain; `None` stays put
+    fn plan(&self, _entity: &Entity, _world: &WorldView) -> Option<SteeringPlan> {
+        None
+    }
+
+    /// Act on another entity within interaction range, returning what
+    /// happened. Called for both orders of every touching pair that
+    /// involves the species.
+    fn interact(&self, _entity: &mut Entity, _other: &mut Entity) -> Option<InteractionKind> {
+        None
+    }
+
+    /// Whether entities regrow from soil fertility the way resources do
+    fn draws_on_soil(&self) -> bool {
+        false
+    }
+
+    /// Whether dead entities leave a carcass, as built-in animals do
+    fn leaves_carcass(&self) -> bool {
+        false
+    }
+
+    /// Draw an entity; return `false` to draw the default disc instead
+    fn draw(&self, _entity: &Entity, _renderer: &mut Renderer) -> bool {
+        false
+    }
+}
+
+/// Custom species known to a simulation, by name
+#[derive(Clone, Default)]
+pub struct SpeciesRegistry {
+    species: BTreeMap<&'static str, Arc<dyn Species>>,
+}
+
+impl SpeciesRegistry {
+    /// Add a species, returning the entity type its entities will have
+    pub fn register(&mut self, species: Arc<dyn Species>) -> Result<EntityType, String> {
+        let entity_type = EntityType::from_name(species.name());
+        if entity_type.is_builtin() {
+            return Err(format!("Species name '{}' is taken by a built-in type", species.name()));
+        }
+        if self.species.contains_key(entity_type.name()) {
+            return Err(format!("Species '{}' is already registered", species.name()));
+        }
+        self.species.insert(entity_type.name(), species);
+        Ok(entity_type)
+    }
+
+    /// The species behind a custom entity type
+    pub fn get(&self, entity_type: EntityType) -> Option<&dyn Species> {
+        match entity_type {
+            EntityType::Custom(name) => self.species.get(name).map(|s| s.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Entity types of all registered species
+    pub fn types(&self) -> impl Iterator<Item = EntityType> + '_ {
+        self.species.keys().map(|name| EntityType::Custom(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimulationConfig;
+    use crate::simulation::Simulation;
+
+    /// A species that drains a point of energy from whatever it touches
+    struct Leech;
+
+    impl Species for Leech {
+        fn name(&self) -> &'static str {
+            "leech"
+        }
+
+        fn init(&self, entity: &mut Entity) {
+            entity.energy = 10;
+            entity.max_energy = 50;
+        }
+
+        fn initial_population(&self) -> usize {
+            4
+        }
+
+        fn interact(&self, entity: &mut Entity, other: &mut Entity) -> Option<InteractionKind> {
+            (other.energy > 0).then(|| {
+                other.energy -= 1;
+                entity.energy += 1;
+                InteractionKind::Consume
+            })
+        }
+    }
+
+    #[test]
+    fn names_are_interned_once() {
+        let first = EntityType::from_name(&String::from("newt"));
+        let second = EntityType::from_name("newt");
+        assert_eq!(first, second);
+        assert!(std::ptr::eq(first.name(), second.name()));
+        assert_eq!(EntityType::from_name("predator"), EntityType::Predator);
+        assert!(!first.is_builtin());
+    }
+
+    #[test]
+    fn registry_refuses_taken_names() {
+        let mut registry = SpeciesRegistry::default();
+        let leech = registry.register(Arc::new(Leech)).unwrap();
+        assert_eq!(leech, EntityType::Custom("leech"));
+        assert!(registry.register(Arc::new(Leech)).is_err());
+        assert_eq!(registry.get(leech).map(|s| s.name()), Some("leech"));
+        assert!(registry.get(EntityType::Gatherer).is_none());
+        assert_eq!(registry.types().collect::<Vec<_>>(), [leech]);
+
+        struct Impostor;
+        impl Species for Impostor {
+            fn name(&self) -> &'static str {
+                "gatherer"
+            }
+        }
+        assert!(registry.register(Arc::new(Impostor)).is_err());
+    }
+
+    #[test]
+    fn registered_species_join_the_world_and_interact() {
+        let config = SimulationConfig { quiet: true, ..SimulationConfig::default() };
+        let mut simulation = Simulation::with_seed(400, 300, config, 1);
+        let leech = simulation.register_species(Leech).unwrap();
+        assert!(simulation.entity_types().contains(&leech));
+
+        let leeches: Vec<&Entity> = simulation.get_entities().iter().filter(|e| e.entity_type == leech).collect();
+        assert_eq!(leeches.len(), 4);
+        assert!(leeches.iter().all(|e| e.energy == 10 && e.max_energy == 50));
+
+        let (id, position) = (leeches[0].id, leeches[0].position);
+        simulation.spawn_entity(EntityType::Resource, position);
+        simulation.update(1.0 / 60.0);
+        assert!(simulation.entity_by_id(id).unwrap().energy > 10);
+    }
+}
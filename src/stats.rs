@@ -0,0 +1,149 @@
+use crate::disease::HealthState;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Samples per chunk of the series. Copies of a run kept for rewinding share
+/// every full chunk, so only the latest is copied with each
+const CHUNK_SIZE: usize = 64;
+
+/// Counts taken at one moment of a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sample {
+    /// Simulation time in seconds
+    pub time: f32,
+    /// Live entities per type name
+    pub populations: BTreeMap<String, usize>,
+    /// Disease hosts per health state name, for epidemic curves
+    pub health: BTreeMap<String, usize>,
+    /// Entities with lifespans per type and life stage, as `<type>_<stage>`,
+    /// for age pyramids
+    pub stages: BTreeMap<String, usize>,
+    /// Members per faction name
+    pub faction_members: BTreeMap<String, usize>,
+    /// Food stored in each faction's nests
+    pub faction_food: BTreeMap<String, u32>,
+    /// Territory cells held per faction name, and by all predators together
+    /// under `predators`
+    pub territory: BTreeMap<String, usize>,
+    /// Separate predator territories, one per pack or lone predator
+    pub predator_claims: usize,
+}
+
+/// Time series of populations and epidemic counts over a run
+#[derive(Debug, Default)]
+pub struct Stats {
+    chunks: Vec<Arc<Vec<Sample>>>,
+    /// The chunks as one slice, made when first asked for after a sample
+    flattened: OnceLock<Vec<Sample>>,
+    since_last_sample: f32,
+}
+
+impl Clone for Stats {
+    /// Shares the chunks, leaving the flattened copy to be made again if needed
+    fn clone(&self) -> Self {
+        Stats { chunks: self.chunks.clone(), flattened: OnceLock::new(), since_last_sample: self.since_last_sample }
+    }
+}
+
+impl Stats {
+    /// Advance the clock, returning whether `interval` seconds have passed
+    /// since the last sample was due
+    pub fn tick(&mut self, delta_time: f32, interval: f32) -> bool {
+        self.since_last_sample += delta_time;
+        if self.since_last_sample >= interval {
+            self.since_last_sample -= interval;
+            return true;
+        }
+        false
+    }
+
+    /// Add a sample to the series
+    pub fn record(&mut self, sample: Sample) {
+        self.flattened.take();
+        match self.chunks.last_mut() {
+            Some(chunk) if chunk.len() < CHUNK_SIZE => Arc::make_mut(chunk).push(sample),
+            _ => self.chunks.push(Arc::new(vec![sample])),
+        }
+    }
+
+    /// Samples taken so far, oldest first, as one slice. Kept for callers that
+    /// need a slice: the first call after each sample copies the whole series,
+    /// so prefer `iter_samples` in anything run often
+    pub fn samples(&self) -> &[Sample] {
+        self.flattened.get_or_init(|| self.iter_samples().cloned().collect())
+    }
+
+    /// Samples taken so far, oldest first, without gathering them into one slice
+    pub fn iter_samples(&self) -> impl Iterator<Item = &Sample> + '_ {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+
+    /// Number of samples taken so far
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    /// Whether no samples have been taken yet
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Samples as CSV: time, one column per entity type seen during the run,
+    /// one per health state, one per type and life stage, members and stored food per faction, then
+    /// territory cells per claimant and the number of predator territories
+    pub fn to_csv(&self) -> String {
+        let types: BTreeSet<&String> = self.iter_samples().flat_map(|s| s.populations.keys()).collect();
+        let stages: BTreeSet<&String> = self.iter_samples().flat_map(|s| s.stages.keys()).collect();
+        let factions: BTreeSet<&String> = self.iter_samples().flat_map(|s| s.faction_members.keys()).collect();
+        let claimants: BTreeSet<&String> = self.iter_samples().flat_map(|s| s.territory.keys()).collect();
+
+        let mut csv = String::from("time");
+        for name in &types {
+            let _ = write!(csv, ",{}", name);
+        }
+        for name in HealthState::NAMES {
+            let _ = write!(csv, ",{}", name);
+        }
+        for name in &stages {
+            let _ = write!(csv, ",{}", name);
+        }
+        for name in &factions {
+            let _ = write!(csv, ",{}_members,{}_food", name, name);
+        }
+        for name in &claimants {
+            let _ = write!(csv, ",{}_territory", name);
+        }
+        csv.push_str(",predator_claims\n");
+
+        for sample in self.iter_samples() {
+            let _ = write!(csv, "{:.2}", sample.time);
+            for name in &types {
+                let _ = write!(csv, ",{}", sample.populations.get(*name).copied().unwrap_or(0));
+            }
+            for name in HealthState::NAMES {
+                let _ = write!(csv, ",{}", sample.health.get(name).copied().unwrap_or(0));
+            }
+            for name in &stages {
+                let _ = write!(csv, ",{}", sample.stages.get(*name).copied().unwrap_or(0));
+            }
+            for name in &factions {
+                let members = sample.faction_members.get(*name).copied().unwrap_or(0);
+                let food = sample.faction_food.get(*name).copied().unwrap_or(0);
+                let _ = write!(csv, ",{},{}", members, food);
+            }
+            for name in &claimants {
+                let _ = write!(csv, ",{}", sample.territory.get(*name).copied().unwrap_or(0));
+            }
+            let _ = writeln!(csv, ",{}", sample.predator_claims);
+        }
+        csv
+    }
+
+    /// Write the samples to a CSV file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+}
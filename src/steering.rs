@@ -0,0 +1,123 @@
+use crate::entity::Entity;
+use crate::position::{Position, Vec2};
+use rand::Rng;
+
+/// Distance ahead of the entity at which the wander circle sits
+const WANDER_DISTANCE: f32 = 30.0;
+
+/// Radius of the wander circle
+const WANDER_RADIUS: f32 = 15.0;
+
+/// Maximum change of the wander angle per call, in radians
+const WANDER_JITTER: f32 = 0.4;
+
+/// Head straight for a target at full speed.
+///
+/// Every behaviour returns a steering force (desired velocity minus current
+/// velocity), so behaviours compose by weighted addition.
+pub fn seek(entity: &Entity, target: Position) -> Vec2 {
+    let desired = entity.position.vector_to(&target).normalized() * entity.max_speed;
+    desired - entity.velocity
+}
+
+/// Run directly away from a threat at full speed
+pub fn flee(entity: &Entity, threat: Position) -> Vec2 {
+    let desired = threat.vector_to(&entity.position).normalized() * entity.max_speed;
+    desired - entity.velocity
+}
+
+/// Seek a target, slowing down inside `slowing_radius` so the entity stops on it
+pub fn arrive(entity: &Entity, target: Position, slowing_radius: f32) -> Vec2 {
+    let offset = entity.position.vector_to(&target);
+    let distance = offset.length();
+    if distance <= 0.0 {
+        return entity.velocity * -1.0;
+    }
+
+    let speed = if distance < slowing_radius {
+        entity.max_speed * distance / slowing_radius
+    } else {
+        entity.max_speed
+    };
+    offset * (speed / distance) - entity.velocity
+}
+
+/// Meander by steering towards a point jittering on a circle ahead of the entity
+pub fn wander(entity: &mut Entity, rng: &mut impl Rng) -> Vec2 {
+    entity.wander_heading += rng.gen_range(-WANDER_JITTER..=WANDER_JITTER);
+
+    let forward = if entity.velocity.length() > 0.0 {
+        entity.velocity.normalized()
+    } else {
+        Vec2::from_angle(entity.wander_heading)
+    };
+    let circle_centre = entity.position.offset(forward * WANDER_DISTANCE);
+    let target = circle_centre.offset(Vec2::from_angle(entity.wander_heading) * WANDER_RADIUS);
+    seek(entity, target)
+}
+
+/// Seek the point where a moving target is predicted to be
+pub fn pursue(entity: &Entity, target: &Entity) -> Vec2 {
+    seek(entity, predict_position(entity, target))
+}
+
+/// Flee from the point where a moving threat is predicted to be
+pub fn evade(entity: &Entity, threat: &Entity) -> Vec2 {
+    flee(entity, predict_position(entity, threat))
+}
+
+/// Push away from neighbours closer than `radius`, stronger the closer they are
+pub fn separation<'a>(entity: &Entity, neighbours: impl Iterator<Item = &'a Entity>, radius: f32) -> Vec2 {
+    let mut force = Vec2::ZERO;
+
+    for neighbour in neighbours.filter(|n| n.id != entity.id) {
+        let away = neighbour.position.vector_to(&entity.position);
+        let distance = away.length();
+        if distance > 0.0 && distance < radius {
+            force += away.normalized() * ((radius - distance) / radius);
+        }
+    }
+
+    force * entity.max_speed
+}
+
+/// Match the average velocity of neighbours
+pub fn alignment(entity: &Entity, neighbours: &[&Entity]) -> Vec2 {
+    if neighbours.is_empty() {
+        return Vec2::ZERO;
+    }
+
+    let mut average = Vec2::ZERO;
+    for neighbour in neighbours {
+        average += neighbour.velocity;
+    }
+    average = average * (1.0 / neighbours.len() as f32);
+    average.truncated(entity.max_speed) - entity.velocity
+}
+
+/// Steer towards the centre of mass of neighbours
+pub fn cohesion(entity: &Entity, neighbours: &[&Entity]) -> Vec2 {
+    if neighbours.is_empty() {
+        return Vec2::ZERO;
+    }
+
+    let mut centre_x = 0.0;
+    let mut centre_y = 0.0;
+    for neighbour in neighbours {
+        centre_x += neighbour.position.x;
+        centre_y += neighbour.position.y;
+    }
+    let count = neighbours.len() as f32;
+    seek(entity, Position::new(centre_x / count, centre_y / count))
+}
+
+/// Where a moving entity will be by the time the pursuer could reach its current position
+fn predict_position(entity: &Entity, other: &Entity) -> Position {
+    let distance = entity.position.distance_to(&other.position);
+    let look_ahead = if entity.max_speed > 0.0 {
+        (distance / entity.max_speed).min(1.0)
+    } else {
+        0.0
+    };
+    other.position.offset(other.velocity * look_ahead)
+}
@@ -0,0 +1,187 @@
+use crate::config::FactionId;
+use crate::entity::EntityId;
+use crate::position::Position;
+use std::collections::BTreeMap;
+
+/// Who a patch of ground belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Claimant {
+    /// A gatherer faction, marked by its members
+    Faction(FactionId),
+    /// A predator, or a pack marking under its leader's id
+    Predator(EntityId),
+}
+
+impl Claimant {
+    /// Whether two claimants compete for ground: factions with factions,
+    /// predators with predators
+    pub fn rivals(&self, other: &Claimant) -> bool {
+        match (self, other) {
+            (Claimant::Faction(a), Claimant::Faction(b)) => a != b,
+            (Claimant::Predator(a), Claimant::Predator(b)) => a != b,
+            _ => false,
+        }
+    }
+}
+
+/// Ownership of one cell and how fresh its marks are, from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Claim {
+    pub owner: Claimant,
+    pub strength: f32,
+}
+
+/// Scent-marked territories on a coarse grid.
+///
+/// Patrolling owners strengthen their claim on the cell they stand in, or wear
+/// down a rival's until the cell changes hands. Every claim fades over time,
+/// so ground that is no longer patrolled falls vacant.
+#[derive(Debug, Clone)]
+pub struct Territories {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    claims: Vec<Option<Claim>>,
+}
+
+impl Territories {
+    /// Unclaimed ground covering a world, in square cells of `cell_size` pixels
+    pub fn new(world_width: usize, world_height: usize, cell_size: f32) -> Self {
+        let cell_size = cell_size.max(1.0);
+        let columns = (world_width as f32 / cell_size).ceil().max(1.0) as usize;
+        let rows = (world_height as f32 / cell_size).ceil().max(1.0) as usize;
+        Territories { cell_size, columns, rows, claims: vec![None; columns * rows] }
+    }
+
+    /// Index of the cell containing a position, clamped to the grid
+    fn index_of(&self, position: &Position) -> usize {
+        let column = ((position.x / self.cell_size).max(0.0) as usize).min(self.columns - 1);
+        let row = ((position.y / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        row * self.columns + column
+    }
+
+    /// Claim on the cell containing a position, if any
+    pub fn claim_at(&self, position: &Position) -> Option<Claim> {
+        self.claims[self.index_of(position)]
+    }
+
+    /// Whether a position lies in a rival's territory held at least at `threshold`
+    pub fn is_foreign(&self, position: &Position, claimant: Claimant, threshold: f32) -> bool {
+        self.claim_at(position)
+            .is_some_and(|claim| claim.owner.rivals(&claimant) && claim.strength >= threshold)
+    }
+
+    /// Mark the cell containing a position: strengthen the claimant's own or a
+    /// vacant cell, or wear down a rival's and take it over once it runs out
+    pub fn mark(&mut self, position: &Position, claimant: Claimant, amount: f32) {
+        let index = self.index_of(position);
+        let cell = &mut self.claims[index];
+        match cell {
+            Some(claim) if claim.owner == claimant => {
+                claim.strength = (claim.strength + amount).min(1.0);
+            }
+            Some(claim) if claim.owner.rivals(&claimant) => {
+                claim.strength -= amount;
+                if claim.strength <= 0.0 {
+                    *cell = Some(Claim { owner: claimant, strength: -claim.strength });
+                }
+            }
+            // Predators and factions don't contest each other's ground
+            Some(_) => {}
+            None => *cell = Some(Claim { owner: claimant, strength: amount.min(1.0) }),
+        }
+    }
+
+    /// Let every claim fade by `amount`, freeing cells that run out
+    pub fn decay(&mut self, amount: f32) {
+        for cell in &mut self.claims {
+            if let Some(claim) = cell {
+                claim.strength -= amount;
+                if claim.strength <= 0.0 {
+                    *cell = None;
+                }
+            }
+        }
+    }
+
+    /// Cells held by each claimant
+    pub fn areas(&self) -> BTreeMap<Claimant, usize> {
+        let mut areas = BTreeMap::new();
+        for claim in self.claims.iter().flatten() {
+            *areas.entry(claim.owner).or_default() += 1;
+        }
+        areas
+    }
+
+    /// Side length of a cell in pixels
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Every claimed cell as its top-left corner in pixels and its claim
+    pub fn claims(&self) -> impl Iterator<Item = (Position, Claim)> + '_ {
+        self.claims.iter().enumerate().filter_map(|(index, claim)| {
+            let column = index % self.columns;
+            let row = index / self.columns;
+            claim.map(|claim| (Position::new(column as f32 * self.cell_size, row as f32 * self.cell_size), claim))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Claimant = Claimant::Faction(0);
+    const BLUE: Claimant = Claimant::Faction(1);
+    const WOLF: Claimant = Claimant::Predator(7);
+
+    #[test]
+    fn marking_claims_vacant_ground_up_to_full_strength() {
+        let mut territories = Territories::new(200, 200, 40.0);
+        let spot = Position::new(50.0, 50.0);
+        territories.mark(&spot, RED, 0.5);
+        assert_eq!(territories.claim_at(&spot), Some(Claim { owner: RED, strength: 0.5 }));
+
+        territories.mark(&spot, RED, 0.75);
+        assert_eq!(territories.claim_at(&spot).map(|claim| claim.strength), Some(1.0));
+        // Same cell, different spot within it
+        assert_eq!(territories.claim_at(&Position::new(79.0, 41.0)).map(|claim| claim.owner), Some(RED));
+        assert_eq!(territories.claim_at(&Position::new(81.0, 50.0)), None);
+        assert_eq!(territories.areas().get(&RED), Some(&1));
+    }
+
+    #[test]
+    fn rivals_wear_a_claim_down_and_take_it_over() {
+        let mut territories = Territories::new(200, 200, 40.0);
+        let spot = Position::new(50.0, 50.0);
+        territories.mark(&spot, RED, 0.5);
+        assert!(territories.is_foreign(&spot, BLUE, 0.3));
+        assert!(!territories.is_foreign(&spot, RED, 0.3));
+
+        territories.mark(&spot, BLUE, 0.25);
+        assert_eq!(territories.claim_at(&spot), Some(Claim { owner: RED, strength: 0.25 }));
+        assert!(!territories.is_foreign(&spot, BLUE, 0.3));
+
+        // The overshoot becomes the new owner's claim
+        territories.mark(&spot, BLUE, 0.5);
+        assert_eq!(territories.claim_at(&spot), Some(Claim { owner: BLUE, strength: 0.25 }));
+
+        // Predators don't contest a faction's ground
+        territories.mark(&spot, WOLF, 1.0);
+        assert_eq!(territories.claim_at(&spot).map(|claim| claim.owner), Some(BLUE));
+        assert!(!territories.is_foreign(&spot, WOLF, 0.0));
+    }
+
+    #[test]
+    fn unpatrolled_claims_fade_to_vacant() {
+        let mut territories = Territories::new(200, 200, 40.0);
+        territories.mark(&Position::new(10.0, 10.0), RED, 0.5);
+        territories.mark(&Position::new(190.0, 190.0), WOLF, 1.0);
+
+        territories.decay(0.5);
+        assert_eq!(territories.claim_at(&Position::new(10.0, 10.0)), None);
+        let claims: Vec<_> = territories.claims().map(|(corner, claim)| (corner, claim.owner)).collect();
+        assert_eq!(claims, [(Position::new(160.0, 160.0), WOLF)]);
+    }
+}
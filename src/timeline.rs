@@ -0,0 +1,87 @@
+use crate::scripting::{ScriptHost, ScriptState};
+use crate::simulation::Simulation;
+use std::collections::VecDeque;
+
+/// Copy of the world, and of what its script remembers, taken after an update
+#[derive(Clone)]
+pub struct Keyframe {
+    pub simulation: Simulation,
+    pub scripts: Option<ScriptState>,
+}
+
+impl Keyframe {
+    /// Take a world, and what its script remembers, back to the keyframe
+    pub fn restore(self, simulation: &mut Simulation, scripts: Option<&mut ScriptHost>) {
+        *simulation = self.simulation;
+        if let (Some(scripts), Some(state)) = (scripts, &self.scripts) {
+            scripts.restore_state(state);
+        }
+    }
+}
+
+/// Ring buffer of keyframes for rewinding a run.
+///
+/// A keyframe is taken every `interval` updates and the oldest is dropped once
+/// `capacity` are kept. Going back restores a keyframe; running on from there
+/// branches a new future, and the keyframes of the old one are dropped.
+pub struct Timeline {
+    interval: u64,
+    capacity: usize,
+    keyframes: VecDeque<Keyframe>,
+}
+
+impl Timeline {
+    /// An empty timeline keeping `capacity` keyframes `interval` updates apart
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        Timeline { interval: interval.max(1), capacity: capacity.max(1), keyframes: VecDeque::new() }
+    }
+
+    /// Take a keyframe if the simulation is on one of the timeline's ticks
+    /// and past the latest keyframe
+    // `is_multiple_of` would need Rust 1.87
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn capture(&mut self, simulation: &Simulation, scripts: Option<&ScriptHost>) {
+        let tick = simulation.ticks();
+        if tick % self.interval != 0 || self.last_tick().is_some_and(|last| last >= tick) {
+            return;
+        }
+        if self.keyframes.len() == self.capacity {
+            self.keyframes.pop_front();
+        }
+        self.keyframes.push_back(Keyframe {
+            simulation: simulation.clone(),
+            scripts: scripts.map(ScriptHost::save_state),
+        });
+    }
+
+    /// Drop the keyframes taken after `tick`
+    pub fn truncate_after(&mut self, tick: u64) {
+        let keep = self.keyframes.partition_point(|k| k.simulation.ticks() <= tick);
+        self.keyframes.truncate(keep);
+    }
+
+    /// Latest keyframe at or before `tick`
+    pub fn before_tick(&self, tick: u64) -> Option<&Keyframe> {
+        self.keyframes.iter().rev().find(|k| k.simulation.ticks() <= tick)
+    }
+
+    /// Latest keyframe at or before `seconds` of simulated time, or the
+    /// earliest kept when they are all later
+    pub fn before_time(&self, seconds: f32) -> Option<&Keyframe> {
+        self.keyframes
+            .iter()
+            .rev()
+            .find(|k| k.simulation.time() <= seconds)
+            .or_else(|| self.keyframes.front())
+    }
+
+    /// Simulated times of the earliest and latest keyframes
+    pub fn span(&self) -> Option<(f32, f32)> {
+        Some((self.keyframes.front()?.simulation.time(), self.keyframes.back()?.simulation.time()))
+    }
+
+    /// Tick of the latest keyframe
+    pub fn last_tick(&self) -> Option<u64> {
+        self.keyframes.back().map(|k| k.simulation.ticks())
+    }
+}
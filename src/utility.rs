@@ -0,0 +1,373 @@
+use crate::brain::{SteeringPlan, WorldView};
+use crate::entity::{Entity, EntityType, FLEE_SPEED_MULTIPLIER};
+use crate::position::Vec2;
+use crate::steering;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Distance at which eating entities start braking
+const ARRIVE_SLOWING_RADIUS: f32 = 25.0;
+
+/// Seconds without food that count as fully starved for the `time_since_hunt` input
+const HUNGER_TIME_SCALE: f32 = 30.0;
+
+/// Age in seconds that counts as fully aged for the `age` input, for
+/// entities without a lifespan
+const AGE_SCALE: f32 = 180.0;
+
+/// Candidate actions a utility brain chooses between
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UtilityAction {
+    Eat,
+    Flee,
+    Rest,
+    Wander,
+    Reproduce,
+    Hunt,
+}
+
+/// Entity state a consideration can respond to, each normalised to 0..1
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    /// Energy relative to maximum energy
+    Energy,
+    /// 1 when a predator is touching, falling to 0 at the edge of sensing range
+    ThreatProximity,
+    /// 1 when food is touching, falling to 0 at the edge of sensing range
+    FoodProximity,
+    /// 1 when prey is touching, falling to 0 at the edge of sensing range
+    PreyProximity,
+    /// Seconds since the last successful hunt, relative to 30 seconds
+    TimeSinceHunt,
+    /// Age relative to the entity's lifespan, or to 180 seconds if it has none
+    Age,
+}
+
+/// Shape of a response curve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveKind {
+    /// `slope * (x - x_shift)^exponent + y_shift` with exponent 1
+    Linear,
+    /// `slope * (x - x_shift)^exponent + y_shift`
+    Polynomial,
+    /// S-curve of height `exponent` centred on `x_shift` with steepness `slope`, plus `y_shift`
+    Logistic,
+    /// 1 from `x_shift` upwards, otherwise 0
+    Step,
+}
+
+/// Maps an input in 0..1 to a score in 0..1
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseCurve {
+    pub kind: CurveKind,
+    pub slope: f32,
+    pub exponent: f32,
+    pub x_shift: f32,
+    pub y_shift: f32,
+}
+
+impl Default for ResponseCurve {
+    fn default() -> Self {
+        ResponseCurve {
+            kind: CurveKind::Linear,
+            slope: 1.0,
+            exponent: 1.0,
+            x_shift: 0.0,
+            y_shift: 0.0,
+        }
+    }
+}
+
+impl ResponseCurve {
+    fn linear(slope: f32, y_shift: f32) -> Self {
+        ResponseCurve { slope, y_shift, ..Default::default() }
+    }
+
+    fn logistic(steepness: f32, midpoint: f32) -> Self {
+        ResponseCurve { kind: CurveKind::Logistic, slope: steepness, x_shift: midpoint, ..Default::default() }
+    }
+
+    fn step(threshold: f32) -> Self {
+        ResponseCurve { kind: CurveKind::Step, x_shift: threshold, ..Default::default() }
+    }
+
+    /// Evaluate the curve, clamping the result to 0..1
+    pub fn evaluate(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        let y = match self.kind {
+            CurveKind::Linear => self.slope * (x - self.x_shift) + self.y_shift,
+            CurveKind::Polynomial => self.slope * (x - self.x_shift).powf(self.exponent) + self.y_shift,
+            CurveKind::Logistic => {
+                self.exponent / (1.0 + (-self.slope * (x - self.x_shift)).exp()) + self.y_shift
+            }
+            CurveKind::Step => {
+                if x >= self.x_shift {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        };
+        y.clamp(0.0, 1.0)
+    }
+}
+
+/// One input passed through a response curve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Consideration {
+    pub input: Input,
+    #[serde(default)]
+    pub curve: ResponseCurve,
+}
+
+/// How an action is scored: the product of its considerations times its weight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionScoring {
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    #[serde(default)]
+    pub considerations: Vec<Consideration>,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+impl ActionScoring {
+    fn new(weight: f32, considerations: Vec<(Input, ResponseCurve)>) -> Self {
+        ActionScoring {
+            weight,
+            considerations: considerations
+                .into_iter()
+                .map(|(input, curve)| Consideration { input, curve })
+                .collect(),
+        }
+    }
+
+    fn score(&self, inputs: &Inputs) -> f32 {
+        self.considerations
+            .iter()
+            .map(|c| c.curve.evaluate(inputs.get(c.input)))
+            .product::<f32>()
+            * self.weight
+    }
+}
+
+/// Scoring rules for every action an entity type may take
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UtilityProfile {
+    /// Bonus multiplier applied to the current action so brains don't dither
+    pub hysteresis: f32,
+    pub actions: BTreeMap<UtilityAction, ActionScoring>,
+}
+
+impl Default for UtilityProfile {
+    fn default() -> Self {
+        UtilityProfile { hysteresis: 0.15, actions: BTreeMap::new() }
+    }
+}
+
+impl UtilityProfile {
+    /// Built-in scoring for an entity type
+    pub fn for_entity_type(entity_type: EntityType) -> Self {
+        use Input::*;
+        use ResponseCurve as Curve;
+
+        let mut actions = BTreeMap::new();
+        match entity_type {
+            EntityType::Gatherer => {
+                actions.insert(
+                    UtilityAction::Eat,
+                    ActionScoring::new(1.0, vec![(Energy, Curve::linear(-1.0, 1.0)), (FoodProximity, Curve::step(0.01))]),
+                );
+                actions.insert(UtilityAction::Flee, ActionScoring::new(1.5, vec![(ThreatProximity, Curve::logistic(12.0, 0.25))]));
+                actions.insert(
+                    UtilityAction::Rest,
+                    ActionScoring::new(0.5, vec![(Energy, Curve::linear(1.0, 0.0)), (ThreatProximity, Curve::linear(-1.0, 1.0))]),
+                );
+                actions.insert(UtilityAction::Wander, ActionScoring::new(0.25, Vec::new()));
+                actions.insert(
+                    UtilityAction::Reproduce,
+                    ActionScoring::new(1.0, vec![(Energy, Curve::step(0.85)), (Age, Curve::step(0.1))]),
+                );
+            }
+            EntityType::Predator => {
+                actions.insert(
+                    UtilityAction::Hunt,
+                    ActionScoring::new(
+                        1.0,
+                        vec![(PreyProximity, Curve::step(0.01)), (TimeSinceHunt, Curve::linear(0.7, 0.3))],
+                    ),
+                );
+                actions.insert(UtilityAction::Rest, ActionScoring::new(0.4, vec![(Energy, Curve::linear(1.0, 0.0))]));
+                actions.insert(UtilityAction::Wander, ActionScoring::new(0.3, Vec::new()));
+                actions.insert(
+                    UtilityAction::Reproduce,
+                    ActionScoring::new(1.0, vec![(Energy, Curve::step(0.9)), (Age, Curve::step(0.1))]),
+                );
+            }
+            EntityType::Resource | EntityType::Carcass | EntityType::Scavenger | EntityType::Custom(_) => {}
+        }
+
+        UtilityProfile { actions, ..Default::default() }
+    }
+
+    /// Score every action and pick the best, favouring the current one
+    pub fn choose(&self, entity: &Entity, world: &WorldView) -> Option<UtilityAction> {
+        let inputs = Inputs::sense(entity, world);
+
+        self.actions
+            .iter()
+            .map(|(action, scoring)| {
+                let mut score = scoring.score(&inputs);
+                if entity.utility_action == Some(*action) {
+                    score *= 1.0 + self.hysteresis;
+                }
+                (*action, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(action, _)| action)
+    }
+}
+
+/// Carry out an action as steering
+pub fn plan_action(action: UtilityAction, entity: &Entity, world: &WorldView) -> SteeringPlan {
+    match action {
+        UtilityAction::Eat => match entity.find_closest_entity(world.entities, EntityType::Resource, world.obstacles) {
+            Some(food) => SteeringPlan::steer(
+                steering::arrive(entity, food.position, ARRIVE_SLOWING_RADIUS),
+                entity.max_speed * 0.8,
+            ),
+            None => SteeringPlan::wander(Vec2::ZERO, entity.max_speed * 0.5),
+        },
+        UtilityAction::Hunt => match entity.find_closest_entity(world.entities, EntityType::Gatherer, world.obstacles) {
+            Some(prey) => SteeringPlan::steer(steering::pursue(entity, prey), entity.max_speed),
+            None => SteeringPlan::wander(Vec2::ZERO, entity.max_speed * 0.5),
+        },
+        UtilityAction::Flee => {
+            let mut force = Vec2::ZERO;
+            for predator in entity.sensed_predators(world.entities, world.obstacles) {
+                force += steering::evade(entity, predator);
+            }
+            SteeringPlan {
+                fleeing: true,
+                ..SteeringPlan::steer(force, entity.max_speed * FLEE_SPEED_MULTIPLIER)
+            }
+        }
+        UtilityAction::Rest => SteeringPlan::steer(entity.velocity * -1.0, 0.0),
+        UtilityAction::Wander => SteeringPlan::wander(Vec2::ZERO, entity.max_speed * 0.5),
+        UtilityAction::Reproduce => SteeringPlan {
+            reproduce: true,
+            ..SteeringPlan::steer(entity.velocity * -1.0, 0.0)
+        },
+    }
+}
+
+/// Normalised inputs sensed once per decision
+struct Inputs {
+    energy: f32,
+    threat_proximity: f32,
+    food_proximity: f32,
+    prey_proximity: f32,
+    time_since_hunt: f32,
+    age: f32,
+}
+
+impl Inputs {
+    fn sense(entity: &Entity, world: &WorldView) -> Self {
+        let proximity = |other: Option<&Entity>| {
+            other.map_or(0.0, |other| {
+                1.0 - (entity.position.distance_to(&other.position) / entity.sensing_radius).min(1.0)
+            })
+        };
+        let nearest_threat = entity
+            .sensed_predators(world.entities, world.obstacles)
+            .into_iter()
+            .min_by(|a, b| {
+                entity
+                    .position
+                    .distance_squared_to(&a.position)
+                    .total_cmp(&entity.position.distance_squared_to(&b.position))
+            });
+
+        Inputs {
+            energy: entity.energy as f32 / entity.max_energy as f32,
+            threat_proximity: proximity(nearest_threat),
+            food_proximity: proximity(entity.find_closest_entity(world.entities, EntityType::Resource, world.obstacles)),
+            prey_proximity: proximity(entity.find_closest_entity(world.entities, EntityType::Gatherer, world.obstacles)),
+            time_since_hunt: entity.time_since_last_hunt / HUNGER_TIME_SCALE,
+            age: if entity.lifespan > 0.0 { entity.age / entity.lifespan } else { entity.age / AGE_SCALE },
+        }
+    }
+
+    fn get(&self, input: Input) -> f32 {
+        match input {
+            Input::Energy => self.energy,
+            Input::ThreatProximity => self.threat_proximity,
+            Input::FoodProximity => self.food_proximity,
+            Input::PreyProximity => self.prey_proximity,
+            Input::TimeSinceHunt => self.time_since_hunt,
+            Input::Age => self.age,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Position;
+
+    fn choose(entity: &Entity, others: &[Entity]) -> Option<UtilityAction> {
+        let entities: Vec<Entity> = std::iter::once(entity.clone()).chain(others.iter().cloned()).collect();
+        let world = WorldView { entities: &entities, obstacles: &[], time: 0.0 };
+        UtilityProfile::for_entity_type(entity.entity_type).choose(entity, &world)
+    }
+
+    fn gatherer(energy: u32) -> Entity {
+        Entity { energy, ..Entity::new_gatherer(1, Position::new(100.0, 100.0)) }
+    }
+
+    #[test]
+    fn curves_stay_within_bounds() {
+        assert_eq!(ResponseCurve::linear(-1.0, 1.0).evaluate(0.25), 0.75);
+        assert_eq!(ResponseCurve::linear(2.0, 0.0).evaluate(0.9), 1.0);
+        assert_eq!(ResponseCurve::step(0.5).evaluate(0.49), 0.0);
+        assert_eq!(ResponseCurve::step(0.5).evaluate(0.5), 1.0);
+        assert!((ResponseCurve::logistic(12.0, 0.25).evaluate(0.25) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gatherers_flee_eat_or_rest_as_their_situation_demands() {
+        let food = Entity::new_resource(2, Position::new(130.0, 100.0));
+        let predator = Entity::new_predator(3, Position::new(120.0, 100.0));
+        assert_eq!(choose(&gatherer(100), &[food.clone(), predator]), Some(UtilityAction::Flee));
+        assert_eq!(choose(&gatherer(40), &[food]), Some(UtilityAction::Eat));
+        assert_eq!(choose(&gatherer(160), &[]), Some(UtilityAction::Rest));
+        assert_eq!(choose(&gatherer(20), &[]), Some(UtilityAction::Wander));
+    }
+
+    #[test]
+    fn hysteresis_keeps_a_slightly_worse_current_action() {
+        // Resting scores 0.5 · 0.46 = 0.23 against wandering's 0.25
+        let entity = gatherer(92);
+        assert_eq!(choose(&entity, &[]), Some(UtilityAction::Wander));
+        let resting = Entity { utility_action: Some(UtilityAction::Rest), ..entity };
+        assert_eq!(choose(&resting, &[]), Some(UtilityAction::Rest));
+    }
+
+    #[test]
+    fn age_is_relative_to_the_lifespan() {
+        let adult = Entity { age: 15.0, ..gatherer(190) };
+        assert_eq!(choose(&adult, &[]), Some(UtilityAction::Rest));
+        let short_lived = Entity { lifespan: 100.0, ..adult.clone() };
+        assert_eq!(choose(&short_lived, &[]), Some(UtilityAction::Reproduce));
+        let long_lived = Entity { lifespan: 1000.0, ..adult };
+        assert_eq!(choose(&long_lived, &[]), Some(UtilityAction::Rest));
+    }
+}
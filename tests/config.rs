@@ -0,0 +1,13 @@
+use rustcolony::config::SimulationConfig;
+
+fn validate(settings: &str) -> Result<(), String> {
+    toml::from_str::<SimulationConfig>(settings).unwrap().validate()
+}
+
+#[test]
+fn mutation_settings_are_checked() {
+    assert!(validate("[neural]\nmutation_rate = 0.5\nmutation_strength = 0.2").is_ok());
+    assert!(validate("[neural]\nmutation_rate = nan").is_err());
+    assert!(validate("[neural]\nmutation_rate = 1.5").is_err());
+    assert!(validate("[neural]\nmutation_strength = -1.0").is_err());
+}
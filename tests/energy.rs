@@ -0,0 +1,93 @@
+use rustcolony::config::SimulationConfig;
+use rustcolony::simulation::Simulation;
+
+/// Run a seeded world for `ticks` fixed updates in strict mode, which panics
+/// on any wrong-way flow, and check the ledger explains the whole change in stock
+fn run_strict(settings: &str, seed: u64, ticks: usize) {
+    let mut config: SimulationConfig = toml::from_str(settings).unwrap();
+    config.energy.strict = true;
+    let tolerance = config.energy.tolerance;
+    let mut simulation = Simulation::with_seed(800, 600, config, seed);
+    let initial = simulation.energy_stock();
+
+    for _ in 0..ticks {
+        simulation.update(1.0 / 60.0);
+    }
+
+    let ledger = simulation.energy_ledger();
+    let flows: f64 = ledger.totals().values().sum();
+    let unaccounted = simulation.energy_stock() - initial - flows;
+    assert!(unaccounted.abs() <= tolerance, "unaccounted energy {:.4}", unaccounted);
+    assert!(!ledger.totals().is_empty());
+}
+
+#[test]
+fn classic_preset_balances() {
+    run_strict(
+        r#"
+        preset = "classic"
+        [carcasses]
+        enabled = true
+        initial_scavengers = 3
+    "#,
+        1,
+        3600,
+    );
+}
+
+#[test]
+fn food_web_preset_balances() {
+    run_strict(
+        r#"
+        preset = "food_web"
+        [carcasses]
+        enabled = true
+        initial_scavengers = 3
+    "#,
+        2,
+        3600,
+    );
+}
+
+#[test]
+fn factions_with_every_subsystem_balance() {
+    let settings = r#"
+        obstacles = 4
+        [flocking]
+        enabled = true
+        [packs]
+        enabled = true
+        [nests]
+        enabled = true
+        [pheromones]
+        enabled = true
+        [territory]
+        enabled = true
+        [disease]
+        enabled = true
+        [carcasses]
+        enabled = true
+        [life]
+        enabled = true
+        [[factions]]
+        name = "red"
+        color = 0xE04040
+        gatherers = 8
+        aggression = 5
+        [[factions]]
+        name = "blue"
+        color = 0x4060E0
+        gatherers = 8
+    "#;
+    run_strict(settings, 3, 3600);
+}
+
+#[test]
+fn report_without_audit_says_nothing_was_audited() {
+    let mut simulation = Simulation::with_seed(800, 600, SimulationConfig::default(), 4);
+    for _ in 0..60 {
+        simulation.update(1.0 / 60.0);
+    }
+    assert!(simulation.energy_ledger().totals().is_empty());
+    assert!(simulation.energy_ledger().report().contains("no update was audited"));
+}
@@ -0,0 +1,83 @@
+use rustcolony::config::SimulationConfig;
+use rustcolony::entity::{EntityId, EntityType};
+use rustcolony::interactions::InteractionKind;
+use rustcolony::position::Position;
+use rustcolony::simulation::{SimEvent, Simulation};
+
+const TIME_STEP: f32 = 1.0 / 60.0;
+
+/// An empty world with a warlike "red" and a peaceful "blue" faction
+fn rivals(reinforce: bool) -> Simulation {
+    let config: SimulationConfig = toml::from_str(&format!(
+        r#"
+        quiet = true
+        [[factions]]
+        name = "red"
+        aggression = 5
+        reinforce = {0}
+        [[factions]]
+        name = "blue"
+        aggression = 0
+        reinforce = {0}
+    "#,
+        reinforce
+    ))
+    .unwrap();
+    let mut simulation = Simulation::with_seed(800, 600, config, 13);
+    let ids: Vec<_> = simulation.get_entities().iter().map(|e| e.id).collect();
+    for id in ids {
+        simulation.remove_entity(id);
+    }
+    simulation
+}
+
+fn members(simulation: &Simulation, faction: usize) -> usize {
+    simulation.get_entities().iter().filter(|e| e.faction == Some(faction)).count()
+}
+
+fn energy(simulation: &Simulation, id: EntityId) -> u32 {
+    simulation.entity_by_id(id).unwrap().energy
+}
+
+#[test]
+fn aggressive_factions_strike_rivals_once_a_second() {
+    let mut simulation = rivals(false);
+    let spot = Position::new(400.0, 300.0);
+    let red = simulation.spawn_in_faction(EntityType::Gatherer, spot, Some(0));
+    let blue = simulation.spawn_in_faction(EntityType::Gatherer, spot, Some(1));
+    simulation.record_events(true);
+
+    simulation.update(TIME_STEP);
+    let (red_energy, blue_energy) = (energy(&simulation, red), energy(&simulation, blue));
+    assert!(blue_energy + 5 <= red_energy, "red {} blue {}", red_energy, blue_energy);
+    let fights: Vec<_> = simulation
+        .take_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            SimEvent::Interaction { kind: InteractionKind::Other("fight"), actor, target } => Some((actor, target)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(fights, [(red, blue)]);
+
+    // Still touching, but the next blow has to wait out the cooldown
+    for _ in 0..6 {
+        simulation.update(TIME_STEP);
+    }
+    assert!(!simulation
+        .take_events()
+        .iter()
+        .any(|event| matches!(event, SimEvent::Interaction { kind: InteractionKind::Other("fight"), .. })));
+}
+
+#[test]
+fn reinforcement_keeps_a_faction_alive() {
+    for reinforce in [true, false] {
+        let mut simulation = rivals(reinforce);
+        for _ in 0..(6.0 / TIME_STEP) as usize {
+            simulation.update(TIME_STEP);
+        }
+        let expected = if reinforce { 2 } else { 0 };
+        assert_eq!((members(&simulation, 0), members(&simulation, 1)), (expected, expected));
+    }
+}
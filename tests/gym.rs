@@ -0,0 +1,69 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustcolony::gym::{AgentAction, ColonyEnv, EnvConfig, StepResult, DISCRETE_ACTIONS};
+
+/// Run an episode of up to `steps` steps with actions drawn from `policy_seed`
+fn run_episode(seed: u64, policy_seed: u64, steps: usize) -> Vec<StepResult> {
+    let mut env = ColonyEnv::new(EnvConfig::default(), seed);
+    let mut policy = StdRng::seed_from_u64(policy_seed);
+    let mut results = Vec::new();
+    for step in 0..steps {
+        let action = if step % 2 == 0 {
+            AgentAction::Discrete(policy.gen_range(0..DISCRETE_ACTIONS))
+        } else {
+            AgentAction::Continuous { x: policy.gen_range(-1.0..1.0), y: policy.gen_range(-1.0..1.0) }
+        };
+        let result = env.step(action).unwrap();
+        results.push(result);
+        if result.terminated || result.truncated {
+            break;
+        }
+    }
+    results
+}
+
+#[test]
+fn same_seed_and_actions_give_identical_episodes() {
+    let first = run_episode(4, 3, 200);
+    let second = run_episode(4, 3, 200);
+    assert!(first.len() > 20, "episode ended after {} steps", first.len());
+    assert_eq!(first, second);
+}
+
+#[test]
+fn different_seeds_give_different_episodes() {
+    assert_ne!(run_episode(4, 3, 50), run_episode(8, 3, 50));
+}
+
+#[test]
+fn invalid_actions_are_rejected_without_stepping() {
+    let mut env = ColonyEnv::new(EnvConfig::default(), 5);
+    let before = env.observe();
+
+    assert!(env.step(AgentAction::Discrete(DISCRETE_ACTIONS)).is_err());
+    assert!(env.step(AgentAction::Continuous { x: f32::NAN, y: 0.0 }).is_err());
+    assert!(env.step(AgentAction::Continuous { x: 0.0, y: f32::INFINITY }).is_err());
+
+    assert_eq!(env.steps(), 0);
+    assert_eq!(env.observe(), before);
+    assert!(env.observe().iter().all(|value| value.is_finite()));
+}
+
+#[test]
+fn continuous_actions_are_clamped_to_top_speed() {
+    let direction = AgentAction::Continuous { x: 30.0, y: 40.0 }.direction().unwrap();
+    assert!((direction.length() - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn logging_does_not_change_episodes() {
+    let mut settings = EnvConfig::default();
+    assert!(settings.simulation.quiet);
+    let mut quiet = ColonyEnv::new(settings.clone(), 4);
+    settings.simulation.quiet = false;
+    let mut chatty = ColonyEnv::new(settings, 4);
+    for step in 0..50 {
+        let action = AgentAction::Discrete(step % DISCRETE_ACTIONS);
+        assert_eq!(quiet.step(action), chatty.step(action));
+    }
+}
@@ -0,0 +1,59 @@
+use rustcolony::brain::SteeringPlan;
+use rustcolony::config::{MetabolismConfig, SimulationConfig};
+use rustcolony::entity::EntityType;
+use rustcolony::position::{Position, Vec2};
+use rustcolony::simulation::Simulation;
+
+const TIME_STEP: f32 = 1.0 / 60.0;
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-5
+}
+
+#[test]
+fn costs_follow_rest_movement_speed_and_mass() {
+    let settings = MetabolismConfig::default();
+    let resting = settings.basal_rate * settings.rest_factor;
+    assert!(close(settings.rate(1, 0.0, 50.0), resting));
+    // Just at walking pace the full basal rate applies
+    let walking = settings.basal_rate + settings.movement_cost * settings.rest_speed
+        + settings.speed_cost * (settings.rest_speed / 50.0).powi(2);
+    assert!(close(settings.rate(1, settings.rest_speed, 50.0), walking));
+    let sprinting = settings.basal_rate + settings.movement_cost * 50.0 + settings.speed_cost;
+    assert!(close(settings.rate(1, 50.0, 50.0), sprinting));
+
+    // Kleiber scaling: sixteen times the size costs eight times as much
+    assert!(close(settings.rate(16, 0.0, 50.0), 8.0 * resting));
+    assert!(close(settings.rate(16, 50.0, 50.0), 8.0 * sprinting));
+}
+
+/// Energy a lone gatherer has burned, paid or still owed, after four seconds
+/// of following a plan
+fn burned(plan: SteeringPlan) -> f32 {
+    let config = SimulationConfig { quiet: true, ..SimulationConfig::default() };
+    let mut simulation = Simulation::with_seed(800, 600, config, 17);
+    let ids: Vec<_> = simulation.get_entities().iter().map(|e| e.id).collect();
+    for id in ids {
+        simulation.remove_entity(id);
+    }
+    let id = simulation.add_controlled_entity(EntityType::Gatherer);
+    simulation.entity_by_id_mut(id).unwrap().position = Position::new(100.0, 300.0);
+    simulation.set_control(id, plan);
+    let start = simulation.entity_by_id(id).unwrap().energy;
+
+    for _ in 0..240 {
+        simulation.update(TIME_STEP);
+    }
+    let gatherer = simulation.entity_by_id(id).unwrap();
+    (start - gatherer.energy) as f32 + gatherer.exertion
+}
+
+#[test]
+fn moving_fast_burns_more_than_resting() {
+    let settings = MetabolismConfig::default();
+    let resting = burned(SteeringPlan::steer(Vec2::ZERO, 0.0));
+    assert!((resting - settings.rate(3, 0.0, 50.0) * 4.0).abs() < 0.01, "resting burned {}", resting);
+
+    let sprinting = burned(SteeringPlan::steer(Vec2::new(1000.0, 0.0), 50.0));
+    assert!(sprinting > 4.0 * resting, "sprinting burned {} to resting {}", sprinting, resting);
+}
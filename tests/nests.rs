@@ -0,0 +1,66 @@
+use rustcolony::config::SimulationConfig;
+use rustcolony::entity::{EntityId, EntityType};
+use rustcolony::life::LifeStage;
+use rustcolony::simulation::Simulation;
+
+/// A world holding one nest and a single well-fed member standing in it with
+/// `carrying` food to drop off
+fn nest_with_carrier(spawn_cost: u32, carrying: u32) -> (Simulation, EntityId) {
+    let config: SimulationConfig = toml::from_str(&format!(
+        r#"
+        quiet = true
+        [nests]
+        enabled = true
+        count = 1
+        spawn_cost = {}
+        [life]
+        enabled = true
+    "#,
+        spawn_cost
+    ))
+    .unwrap();
+    let mut simulation = Simulation::with_seed(800, 600, config, 11);
+    let ids: Vec<_> = simulation.get_entities().iter().map(|e| e.id).collect();
+    for id in ids {
+        simulation.remove_entity(id);
+    }
+
+    let home = simulation.get_nests()[0].position;
+    let carrier = simulation.spawn_entity(EntityType::Gatherer, home);
+    let entity = simulation.entity_by_id_mut(carrier).unwrap();
+    entity.energy = entity.max_energy;
+    entity.carrying = carrying;
+    (simulation, carrier)
+}
+
+#[test]
+fn nests_raise_juveniles_from_stored_food() {
+    let (mut simulation, carrier) = nest_with_carrier(120, 150);
+    simulation.update(1.0 / 60.0);
+
+    let nest = &simulation.get_nests()[0];
+    assert_eq!((nest.raised, nest.food), (1, 30));
+    let newborn = simulation
+        .get_entities()
+        .iter()
+        .find(|e| e.entity_type == EntityType::Gatherer && e.id != carrier)
+        .unwrap();
+    let juvenile = simulation.config().life.juvenile.metabolism;
+    assert_eq!(newborn.nest_id, Some(nest.id));
+    assert_eq!(newborn.energy, 120);
+    assert!(newborn.age < 0.1);
+    assert_eq!(newborn.stage, LifeStage::Juvenile);
+    assert_eq!(newborn.metabolism, juvenile);
+}
+
+#[test]
+fn food_a_newborn_cannot_hold_stays_in_the_store() {
+    let (mut simulation, carrier) = nest_with_carrier(300, 300);
+    simulation.update(1.0 / 60.0);
+
+    let newborn = simulation.get_entities().iter().find(|e| e.id != carrier).unwrap();
+    assert_eq!(newborn.energy, newborn.max_energy);
+    let nest = &simulation.get_nests()[0];
+    assert_eq!(nest.raised, 1);
+    assert_eq!(nest.food, 300 - newborn.max_energy);
+}
@@ -0,0 +1,38 @@
+use rustcolony::config::{SimulationConfig, MAX_OBSTACLES};
+use rustcolony::simulation::Simulation;
+
+fn coverage(simulation: &Simulation) -> f32 {
+    let (width, height) = simulation.world_size();
+    let covered: f32 = simulation.get_obstacles().iter().map(|o| std::f32::consts::PI * o.radius * o.radius).sum();
+    covered / (width * height) as f32
+}
+
+#[test]
+fn dense_obstacles_start_and_run() {
+    let config = SimulationConfig { obstacles: 10_000, ..SimulationConfig::default() };
+    let mut simulation = Simulation::with_seed(800, 600, config, 2);
+    assert!(simulation.get_obstacles().len() < 10_000);
+    assert!(coverage(&simulation) <= 0.5);
+
+    simulation.add_random_gatherers(20);
+    simulation.add_random_resources(20);
+    for _ in 0..60 {
+        simulation.update(1.0 / 60.0);
+    }
+    assert_eq!(simulation.ticks(), 60);
+}
+
+#[test]
+fn obstacles_never_cover_most_of_a_tiny_world() {
+    let config = SimulationConfig { obstacles: 50, ..SimulationConfig::default() };
+    let simulation = Simulation::with_seed(60, 60, config, 3);
+    assert!(coverage(&simulation) <= 0.5);
+}
+
+#[test]
+fn too_many_obstacles_fail_validation() {
+    let mut config = SimulationConfig { obstacles: MAX_OBSTACLES, ..SimulationConfig::default() };
+    assert!(config.validate().is_ok());
+    config.obstacles += 1;
+    assert!(config.validate().is_err());
+}
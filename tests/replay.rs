@@ -0,0 +1,87 @@
+use rustcolony::config::SimulationConfig;
+use rustcolony::replay::{Intervention, Replay};
+use rustcolony::scripting::ScriptHost;
+use rustcolony::simulation::Simulation;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const TIME_STEP: f32 = 1.0 / 60.0;
+
+/// Everything about the world's entities, for comparing two runs
+fn state(simulation: &Simulation) -> String {
+    format!("{} {:?}", simulation.ticks(), simulation.get_entities())
+}
+
+fn scratch_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rustcolony-{}-{}", std::process::id(), name))
+}
+
+/// Re-run a replay from the start to its last tick
+fn play(replay: &Replay) -> Simulation {
+    let mut simulation = replay.start();
+    let mut scripts = replay.scripts(&mut simulation).unwrap();
+    while simulation.ticks() < replay.ticks {
+        replay.step(&mut simulation, scripts.as_mut());
+    }
+    simulation
+}
+
+#[test]
+fn replay_repeats_a_recorded_run() {
+    let mut simulation = Simulation::with_seed(800, 600, SimulationConfig::default(), 3);
+    let mut replay = Replay::record(&simulation, TIME_STEP);
+    for tick in 0..1200 {
+        match tick {
+            200 => replay.push(&simulation, Intervention::AddPredators { count: 2 }),
+            500 => replay.push(&simulation, Intervention::AddGatherers { count: 10 }),
+            501 => replay.push(&simulation, Intervention::AddResources { count: 20 }),
+            _ => {}
+        }
+        replay.step(&mut simulation, None);
+    }
+    replay.ticks = simulation.ticks();
+
+    let path = scratch_file("replay.json");
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.events, replay.events);
+    assert_eq!(state(&play(&loaded)), state(&simulation));
+}
+
+#[test]
+fn replay_ignores_later_script_edits() {
+    let path = scratch_file("script.rhai");
+    std::fs::write(
+        &path,
+        r#"fn on_tick(world, dt) {
+            if world.random(0.0, 1.0) < 0.05 {
+                world.spawn_entity("resource", world.random(0.0, 800.0), world.random(0.0, 600.0));
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let mut config = SimulationConfig::default();
+    config.scripting.file = Some(path.clone());
+    config.scripting.hot_reload = true;
+    let mut simulation = Simulation::with_seed(800, 600, config, 5);
+    let mut scripts = ScriptHost::load(path.clone(), &mut simulation).unwrap();
+    let mut replay = Replay::record(&simulation, TIME_STEP);
+    replay.keep_script(&mut scripts);
+
+    for tick in 0..600 {
+        if tick == 300 {
+            // An edit mid-recording must not be picked up, as playback couldn't repeat it
+            std::fs::write(&path, r#"fn on_tick(world, dt) { world.spawn_entity("predator", 10.0, 10.0); }"#).unwrap();
+            std::thread::sleep(Duration::from_millis(600));
+        }
+        replay.step(&mut simulation, Some(&mut scripts));
+    }
+    replay.ticks = simulation.ticks();
+
+    let replayed = play(&replay);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(state(&replayed), state(&simulation));
+}
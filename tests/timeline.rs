@@ -0,0 +1,85 @@
+use rustcolony::config::SimulationConfig;
+use rustcolony::replay::{Intervention, Replay};
+use rustcolony::simulation::Simulation;
+use rustcolony::timeline::Timeline;
+
+const TIME_STEP: f32 = 1.0 / 60.0;
+
+/// Everything about the world's entities and its stats, for comparing two runs
+fn state(simulation: &Simulation) -> String {
+    let samples: Vec<_> = simulation.stats().iter_samples().collect();
+    format!("{} {:?} {:?}", simulation.ticks(), simulation.get_entities(), samples)
+}
+
+#[test]
+fn running_on_from_a_keyframe_matches_a_straight_run() {
+    let mut simulation = Simulation::with_seed(800, 600, SimulationConfig::default(), 7);
+    let mut timeline = Timeline::new(60, 10);
+    let mut straight = None;
+    while simulation.ticks() < 1200 {
+        simulation.update(TIME_STEP);
+        timeline.capture(&simulation, None);
+        if simulation.ticks() == 1000 {
+            straight = Some(state(&simulation));
+        }
+    }
+
+    // Ten keyframes are kept, so the earliest is at tick 660
+    assert_eq!(timeline.before_tick(600).map(|k| k.simulation.ticks()), None);
+    assert_eq!(timeline.last_tick(), Some(1200));
+    let keyframe = timeline.before_tick(959).unwrap().clone();
+    assert_eq!(keyframe.simulation.ticks(), 900);
+    assert_eq!(keyframe.simulation.stats().len(), 15);
+
+    keyframe.restore(&mut simulation, None);
+    while simulation.ticks() < 1000 {
+        simulation.update(TIME_STEP);
+    }
+    assert_eq!(Some(state(&simulation)), straight);
+}
+
+#[test]
+fn branching_drops_the_old_future() {
+    let mut simulation = Simulation::with_seed(800, 600, SimulationConfig::default(), 9);
+    let mut replay = Replay::record(&simulation, TIME_STEP);
+    let mut timeline = Timeline::new(60, 100);
+    while simulation.ticks() < 1200 {
+        match simulation.ticks() {
+            300 => replay.push(&simulation, Intervention::AddPredators { count: 1 }),
+            900 => replay.push(&simulation, Intervention::AddGatherers { count: 10 }),
+            _ => {}
+        }
+        replay.step(&mut simulation, None);
+        timeline.capture(&simulation, None);
+    }
+
+    // Rewind to the keyframe at 10 seconds and branch from there
+    timeline.before_time(10.5).unwrap().clone().restore(&mut simulation, None);
+    let tick = simulation.ticks();
+    assert_eq!(tick, 600);
+    timeline.truncate_after(tick);
+    replay.truncate(tick);
+
+    assert_eq!(timeline.last_tick(), Some(600));
+    assert_eq!(timeline.before_tick(1200).map(|k| k.simulation.ticks()), Some(600));
+    assert_eq!(timeline.span().map(|(_, end)| end), Some(simulation.time()));
+    assert_eq!(replay.ticks, 600);
+    assert_eq!(replay.events.iter().map(|e| e.tick).collect::<Vec<_>>(), [300]);
+
+    // The new future records and replays like any other
+    while simulation.ticks() < 1200 {
+        if simulation.ticks() == 700 {
+            replay.push(&simulation, Intervention::AddResources { count: 30 });
+        }
+        replay.step(&mut simulation, None);
+        timeline.capture(&simulation, None);
+    }
+    replay.ticks = simulation.ticks();
+    assert_eq!(timeline.last_tick(), Some(1200));
+
+    let mut replayed = replay.start();
+    while replayed.ticks() < replay.ticks {
+        replay.step(&mut replayed, None);
+    }
+    assert_eq!(state(&replayed), state(&simulation));
+}